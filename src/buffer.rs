@@ -26,6 +26,8 @@ pub struct StrBuf<'a> {
 	memory: &'a mut [MaybeUninit<u8>],
 	initialized: usize,
 	filled: usize,
+	/// Whether line endings in the filled data are normalized already, so that text is shifted out as is.
+	line_endings_normalized: bool,
 }
 
 impl Debug for StrBuf<'_> {
@@ -85,9 +87,17 @@ impl<'a> StrBuf<'a> {
 			memory,
 			initialized: 0,
 			filled: 0,
+			line_endings_normalized: false,
 		}
 	}
 
+	/// Sets whether line endings in the data are normalized already, like in an entity's replacement text.
+	///
+	/// [`LineEndings`] passed to methods of this buffer are then ignored.
+	pub(crate) fn set_line_endings_normalized(&mut self, line_endings_normalized: bool) {
+		self.line_endings_normalized = line_endings_normalized;
+	}
+
 	#[must_use]
 	pub fn filled(&self) -> &[u8] {
 		unsafe { &*(addr_of!(self.memory[0..self.filled]) as *const [u8]) }
@@ -249,6 +259,7 @@ impl<'a> StrBuf<'a> {
 	pub fn shift_chars_while(
 		&mut self,
 		mut predicate: impl FnMut(char) -> bool,
+		line_endings: LineEndings,
 	) -> Result<Result<&'a mut str, Utf8Error>, Indeterminate> {
 		let mut rest = self.filled();
 		let mut chars = BufReadCharsExt::chars_raw(&mut rest);
@@ -283,7 +294,7 @@ impl<'a> StrBuf<'a> {
 
 		Ok(Ok(unsafe {
 			//SAFETY: UTF-8 validation happens above.
-			from_utf8_unchecked_mut(self.shift_filled(len).expect("unreachable"))
+			self.shift_text(len, line_endings)?
 		}))
 	}
 
@@ -298,6 +309,7 @@ impl<'a> StrBuf<'a> {
 		&mut self,
 		start_predicate: impl FnOnce(char) -> bool,
 		mut predicate: impl FnMut(char) -> bool,
+		line_endings: LineEndings,
 	) -> Result<Result<&'a mut str, Utf8Error>, Indeterminate> {
 		let mut rest = self.filled();
 		let mut chars = BufReadCharsExt::chars_raw(&mut rest);
@@ -347,10 +359,9 @@ impl<'a> StrBuf<'a> {
 			};
 		}
 
-		//TODO: Transform line endings!
 		Ok(Ok(unsafe {
 			//SAFETY: UTF-8 validation happens above.
-			from_utf8_unchecked_mut(self.shift_filled(len).expect("unreachable"))
+			self.shift_text(len, line_endings)?
 		}))
 	}
 
@@ -364,6 +375,7 @@ impl<'a> StrBuf<'a> {
 	pub fn shift_chars_delimited(
 		&mut self,
 		delimiter: &[u8],
+		line_endings: LineEndings,
 	) -> Result<Result<&'a mut str, Utf8Error>, Indeterminate> {
 		let data = match self
 			.filled()
@@ -404,10 +416,9 @@ impl<'a> StrBuf<'a> {
 			},
 		};
 
-		//TODO: Transform line endings!
 		Ok(Ok(unsafe {
 			//SAFETY: Validate above.
-			from_utf8_unchecked_mut(self.shift_filled(valid_len).expect("unreachable"))
+			self.shift_text(valid_len, line_endings)?
 		}))
 	}
 
@@ -422,6 +433,7 @@ impl<'a> StrBuf<'a> {
 		&mut self,
 		mut predicate: impl FnMut(char) -> bool,
 		delimiter: &[u8],
+		line_endings: LineEndings,
 	) -> Result<Result<&'a mut str, Utf8Error>, Indeterminate> {
 		let data = match self
 			.filled()
@@ -471,13 +483,75 @@ impl<'a> StrBuf<'a> {
 			},
 		};
 
-		//TODO: Transform line endings!
 		Ok(Ok(unsafe {
 			//SAFETY: Validate above.
-			from_utf8_unchecked_mut(self.shift_filled(valid_len).expect("unreachable"))
+			self.shift_text(valid_len, line_endings)?
 		}))
 	}
 
+	/// Decodes the character starting `offset` bytes into the filled data, without skipping past anything.
+	///
+	/// # Errors
+	///
+	/// Iff this buffer does not contain that entire character, then [`Err<Indeterminate>`] is returned instead.
+	pub fn peek_char(&self, offset: usize) -> Result<Result<char, Utf8Error>, Indeterminate> {
+		let data = self.filled().get(offset..).unwrap_or_default();
+		let data = &data[..min(data.len(), 4)];
+		let valid = match from_utf8(data) {
+			Ok(valid) => valid,
+			Err(error) => match error.valid_up_to() {
+				0 => match error.error_len() {
+					None => return Err(Indeterminate::new()),
+					Some(error_len) => return Ok(Err(Utf8Error { len: error_len })),
+				},
+				len => from_utf8(&data[..len]).expect("unreachable"),
+			},
+		};
+		match valid.chars().next() {
+			Some(c) => Ok(Ok(c)),
+			None => Err(Indeterminate::new()),
+		}
+	}
+
+	/// Skips past `len` bytes of text, normalizing its line endings in place.
+	///
+	/// A `\r` at the end of that text is left in the buffer if what follows it could still turn out to continue the line break,
+	/// so that line breaks split across chunks aren't doubled.
+	///
+	/// # Errors
+	///
+	/// Iff that leaves no text to skip.
+	///
+	/// # Safety
+	///
+	/// The first `len` filled bytes must be valid UTF-8.
+	unsafe fn shift_text(
+		&mut self,
+		mut len: usize,
+		line_endings: LineEndings,
+	) -> Result<&'a mut str, Indeterminate> {
+		if self.line_endings_normalized {
+			let text = self.shift_filled(len).expect("unreachable");
+			//SAFETY: Guaranteed by the caller.
+			return Ok(from_utf8_unchecked_mut(text));
+		}
+
+		if len > 0
+			&& self.filled()[len - 1] == b'\r'
+			&& line_endings.may_continue_carriage_return(&self.filled()[len..])
+		{
+			len -= 1;
+			if len == 0 {
+				return Err(Indeterminate::new());
+			}
+		}
+
+		let text = self.shift_filled(len).expect("unreachable");
+		let len = line_endings.normalize(text);
+		//SAFETY: Line breaks are only ever replaced with `\n` as a whole.
+		Ok(from_utf8_unchecked_mut(&mut text[..len]))
+	}
+
 	/// Returns the number of bytes that can still be inserted into this buffer in the current memory allocation (without resetting it).
 	#[must_use]
 	pub fn remaining_len(&self) -> usize {
@@ -502,6 +576,7 @@ impl<'a> StrBuf<'a> {
 				memory,
 				initialized: self.filled,
 				filled: self.filled,
+				line_endings_normalized: self.line_endings_normalized,
 			})
 		}
 	}
@@ -530,7 +605,10 @@ impl<'a> StrBuf<'a> {
 			.offset_from(self.origin)
 			.try_conv::<usize>()
 			.expect("unreachable"))
-			..(addr_of_mut!(self.memory[self.filled])
+			..(self
+				.memory
+				.as_mut_ptr()
+				.add(self.filled)
 				.offset_from(self.origin)
 				.try_conv::<usize>()
 				.expect("unreachable"));
@@ -552,8 +630,8 @@ impl<'a> StrBuf<'a> {
 		} else {
 			// Fast path.
 			copy_nonoverlapping(
-				addr_of_mut!(self.memory[filled.start]),
-				addr_of_mut!(self.memory[0]),
+				self.memory.as_mut_ptr().add(filled.start),
+				self.memory.as_mut_ptr(),
 				filled.len(),
 			);
 		}
@@ -570,6 +648,48 @@ impl<'a> StrBuf<'a> {
 	}
 }
 
+/// Which line breaks are normalized to `\n` in text shifted out of a [`StrBuf`].
+///
+/// See [2.11 End-of-Line Handling](https://www.w3.org/TR/xml11/#sec-line-ends).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndings {
+	/// `\r\n` and `\r`.
+	Xml1_0,
+	/// Additionally `\r` followed by U+0085, U+0085 and U+2028.
+	Xml1_1,
+}
+
+impl LineEndings {
+	fn may_continue_carriage_return(self, rest: &[u8]) -> bool {
+		match self {
+			Self::Xml1_0 => rest.is_empty(),
+			Self::Xml1_1 => matches!(rest, [] | [0xC2]),
+		}
+	}
+
+	/// Normalizes line breaks in `text` in place, returning its new length.
+	fn normalize(self, text: &mut [u8]) -> usize {
+		let (mut read, mut write) = (0, 0);
+		while read < text.len() {
+			let line_break_len = match (self, &text[read..]) {
+				(_, [b'\r', b'\n', ..]) | (Self::Xml1_1, [0xC2, 0x85, ..]) => 2,
+				(Self::Xml1_1, [b'\r', 0xC2, 0x85, ..] | [0xE2, 0x80, 0xA8, ..]) => 3,
+				(_, [b'\r', ..]) => 1,
+				_ => 0,
+			};
+			if line_break_len > 0 {
+				text[write] = b'\n';
+				read += line_break_len;
+			} else {
+				text[write] = text[read];
+				read += 1;
+			}
+			write += 1;
+		}
+		write
+	}
+}
+
 #[derive(Debug, Error, Diagnostic, PartialEq, Eq)]
 #[error("Invalid UTF-8 encountered.")]
 pub struct Utf8Error {
@@ -0,0 +1,489 @@
+//! Replacement of entity references with their replacement text, within limits.
+
+use crate::{
+	buffer::StrBuf,
+	resolver::{EntityResolver, Refusal},
+	scanner::{Event, MoreInputRequired},
+	well_formedness::{Checker, WellFormednessError},
+};
+use std::{fmt::Write, mem::MaybeUninit};
+
+/// Includes the replacement text of internal entities referenced in content and attribute values,
+/// scanning it with the [`Checker`]'s own [`Scanner`](`crate::scanner::Scanner`).
+///
+/// The [`Event`]s of a replacement text directly follow the [`Event::EntityRefEnd`] of its reference.
+/// References to undeclared entities, where those aren't an error, are reported but not included.
+///
+/// External parsed entities are only included if the caller resolves them when asked to through [`Suspended::ResolveEntity`].
+/// Their text declaration, if any, is scanned separately (as by [`Scanner::new_external_parsed_entity`](`crate::scanner::Scanner::new_external_parsed_entity`))
+/// and isn't reported.
+///
+/// Replacement text is scanned from a second buffer, which the caller provides alongside the input buffer.
+/// Like the input buffer, it should be reset (if at all) only whenever [`MoreInputRequired`] is returned.
+///
+/// The limits default to a depth of 16, no total limit, and an amplification factor of 100 after 1 MiB of replacement text,
+/// which stops both [billion laughs](https://en.wikipedia.org/wiki/Billion_laughs_attack) and quadratic blowup.
+/// The text of resolved external entities counts as input for these limits.
+#[derive(Debug)]
+pub struct Expander {
+	checker: Checker,
+	depth_limit: usize,
+	expanded_bytes_limit: usize,
+	amplification_factor: usize,
+	amplification_threshold: usize,
+	expanded_bytes: usize,
+	input_bytes: usize,
+	/// The entities being included, innermost last.
+	frames: Vec<Frame>,
+	/// Set while scanning the end of an entity's replacement text that continues into the input.
+	bridge: Option<Bridge>,
+	/// How many tags, references and other markup are open.
+	markup_depth: usize,
+	base_uri: Option<Box<str>>,
+	unresolved: Option<Unresolved>,
+}
+
+#[derive(Debug)]
+struct Unresolved {
+	name: Box<str>,
+	public_id: Option<Box<str>>,
+	system_id: Box<str>,
+	/// Whether [`Suspended::ResolveEntity`] was returned for this entity.
+	requested: bool,
+}
+
+/// Why [`Expander::resume`] returned without a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspended {
+	/// Either buffer requires more input or space.
+	MoreInputRequired,
+	/// An external parsed entity was referenced, see [`Expander::unresolved_entity`].
+	///
+	/// It's included if [`Expander::resolve_with`] is called before resuming, and skipped otherwise.
+	ResolveEntity,
+}
+
+impl From<MoreInputRequired> for Suspended {
+	fn from(_: MoreInputRequired) -> Self {
+		Self::MoreInputRequired
+	}
+}
+
+/// The external identifier of an external parsed entity, along with the base URI for its system ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalEntity<'a> {
+	pub name: &'a str,
+	pub public_id: Option<&'a str>,
+	pub system_id: &'a str,
+	pub base_uri: Option<&'a str>,
+}
+
+#[derive(Debug)]
+struct Frame {
+	name: Box<str>,
+	/// The replacement text, escaped for its context.
+	text: Vec<u8>,
+	/// How much of [`Self::text`] was moved into the expansion buffer.
+	offset: usize,
+	/// Whether line endings in [`Self::text`] are normalized already, which is the case for replacement text of internal entities.
+	line_endings_normalized: bool,
+	open_elements: usize,
+	markup_depth: usize,
+}
+
+/// Bytes in the expansion buffer that weren't scanned before the end of an entity.
+/// They're followed by copies of input bytes.
+#[derive(Debug)]
+struct Bridge {
+	/// How many bytes at the start of the expansion buffer are left over from the entity.
+	leftover: usize,
+	/// How many input bytes were copied behind them.
+	copied: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExpansionError {
+	WellFormednessError(WellFormednessError),
+	/// Entities are nested deeper than the depth limit.
+	DepthLimitExceeded,
+	/// The replacement texts included so far are longer than the expanded bytes limit.
+	ExpandedBytesLimitExceeded,
+	/// The replacement texts included so far are longer than the input scanned so far times the amplification factor.
+	AmplificationLimitExceeded,
+	/// An entity's replacement text doesn't contain only complete markup.
+	UnbalancedEntity(Box<str>),
+	/// An external parsed entity wasn't resolved.
+	///
+	/// The reference is skipped, so scanning may continue.
+	Refused {
+		name: Box<str>,
+		refusal: Refusal,
+	},
+}
+
+impl From<WellFormednessError> for ExpansionError {
+	fn from(well_formedness_error: WellFormednessError) -> Self {
+		Self::WellFormednessError(well_formedness_error)
+	}
+}
+
+impl Expander {
+	/// Wraps `checker`, which keeps up to `replacement_text_budget` bytes of replacement text (and external identifiers) for inclusion.
+	#[must_use]
+	pub fn new(mut checker: Checker, replacement_text_budget: usize) -> Self {
+		checker.keep_replacement_texts(replacement_text_budget);
+		Self {
+			checker,
+			depth_limit: 16,
+			expanded_bytes_limit: usize::MAX,
+			amplification_factor: 100,
+			amplification_threshold: 1 << 20,
+			expanded_bytes: 0,
+			input_bytes: 0,
+			frames: Vec::new(),
+			bridge: None,
+			markup_depth: 0,
+			base_uri: None,
+			unresolved: None,
+		}
+	}
+
+	/// Sets how deeply entities may be nested.
+	#[must_use]
+	pub fn with_depth_limit(mut self, depth_limit: usize) -> Self {
+		self.depth_limit = depth_limit;
+		self
+	}
+
+	/// Sets how many bytes of replacement text may be included in total.
+	#[must_use]
+	pub fn with_expanded_bytes_limit(mut self, expanded_bytes_limit: usize) -> Self {
+		self.expanded_bytes_limit = expanded_bytes_limit;
+		self
+	}
+
+	/// Sets how many times longer than the input the included replacement text may be,
+	/// once it's longer than `threshold` bytes.
+	#[must_use]
+	pub fn with_amplification_limit(mut self, factor: usize, threshold: usize) -> Self {
+		self.amplification_factor = factor;
+		self.amplification_threshold = threshold;
+		self
+	}
+
+	/// Sets the base URI of the document, against which relative system IDs are resolved.
+	#[must_use]
+	pub fn with_base_uri(mut self, base_uri: impl Into<Box<str>>) -> Self {
+		self.base_uri = Some(base_uri.into());
+		self
+	}
+
+	/// Like [`Checker::resume`], but with the replacement text of entity references scanned from `expansion_buffer`.
+	///
+	/// # Errors
+	///
+	/// Iff either buffer requires more input or space, or an external parsed entity should be resolved.
+	///
+	/// The inner [`Result`] is an [`Err`] for any well-formedness error and any exceeded limit.
+	pub fn resume<'a>(
+		&mut self,
+		buffer: &mut StrBuf<'a>,
+		expansion_buffer: &mut StrBuf<'a>,
+	) -> Result<Result<Option<Event<'a>>, ExpansionError>, Suspended> {
+		if let Some(unresolved) = &mut self.unresolved {
+			if !unresolved.requested {
+				unresolved.requested = true;
+				return Err(Suspended::ResolveEntity);
+			}
+			self.unresolved = None;
+		}
+
+		loop {
+			let result = if let Some(bridge) = &mut self.bridge {
+				expansion_buffer.set_line_endings_normalized(false);
+				let before = expansion_buffer.filled().len();
+				match self.checker.resume(expansion_buffer) {
+					Ok(result) => {
+						let consumed = before - expansion_buffer.filled().len();
+						if consumed < bridge.leftover {
+							bridge.leftover -= consumed;
+						} else {
+							// Resynchronize with the input, then continue there.
+							let extra = consumed - bridge.leftover;
+							discard(buffer, extra);
+							self.input_bytes += extra;
+							discard(expansion_buffer, expansion_buffer.filled().len());
+							self.bridge = None;
+						}
+						result
+					}
+					Err(more_input_required) => {
+						let copied = append(expansion_buffer, &buffer.filled()[bridge.copied..]);
+						if copied == 0 {
+							return Err(more_input_required.into());
+						}
+						bridge.copied += copied;
+						continue;
+					}
+				}
+			} else if let Some(frame) = self.frames.last_mut() {
+				expansion_buffer.set_line_endings_normalized(frame.line_endings_normalized);
+				match self.checker.resume(expansion_buffer) {
+					Ok(result) => result,
+					Err(more_input_required) => {
+						if frame.offset < frame.text.len() {
+							let copied = append(expansion_buffer, &frame.text[frame.offset..]);
+							if copied == 0 {
+								return Err(more_input_required.into());
+							}
+							frame.offset += copied;
+						} else if let Err(error) = self.end_entity(expansion_buffer) {
+							return Ok(Err(error));
+						}
+						continue;
+					}
+				}
+			} else {
+				let before = buffer.filled().len();
+				let result = self.checker.resume(buffer)?;
+				self.input_bytes += before - buffer.filled().len();
+				result
+			};
+
+			return Ok(match result {
+				Ok(Some(event)) => self.observe(&event, expansion_buffer).map(|()| Some(event)),
+				Ok(None) => Ok(None),
+				Err(error) => Err(error.into()),
+			});
+		}
+	}
+
+	fn observe(
+		&mut self,
+		event: &Event,
+		expansion_buffer: &mut StrBuf,
+	) -> Result<(), ExpansionError> {
+		match event {
+			Event::StartTagStart(_)
+			| Event::EndTagStart(_)
+			| Event::CommentStart(_)
+			| Event::PIStart(_)
+			| Event::CDStart(_)
+			| Event::CharRefDecimalStart(_)
+			| Event::CharRefHexadecimalStart(_)
+			| Event::EntityRefStart(_) => self.markup_depth += 1,
+			Event::StartTagEnd(_)
+			| Event::StartTagEndEmpty(_)
+			| Event::EndTagEnd(_)
+			| Event::CommentEnd(_)
+			| Event::PIEnd(_)
+			| Event::CDEnd(_)
+			| Event::CharRefEnd(..)
+			| Event::EntityRefEnd(_) => self.markup_depth -= 1,
+			_ => (),
+		}
+
+		if let Some((name, in_att_value)) = self.checker.checked_reference() {
+			if let Some(replacement_text) = self.checker.replacement_text(name) {
+				let (name, text) = (name.into(), escape(replacement_text, in_att_value));
+				self.suspend_frame(expansion_buffer);
+				return self.include(name, text.into_bytes(), true);
+			} else if let (false, Some((public_id, system_id))) =
+				(in_att_value, self.checker.external_id(name))
+			{
+				self.unresolved = Some(Unresolved {
+					name: name.into(),
+					public_id: public_id.map(Into::into),
+					system_id: system_id.into(),
+					requested: false,
+				});
+				self.suspend_frame(expansion_buffer);
+			}
+		}
+		Ok(())
+	}
+
+	/// The external parsed entity that should be resolved, after [`Suspended::ResolveEntity`].
+	#[must_use]
+	pub fn unresolved_entity(&self) -> Option<ExternalEntity<'_>> {
+		self.unresolved.as_ref().map(|unresolved| ExternalEntity {
+			name: &unresolved.name,
+			public_id: unresolved.public_id.as_deref(),
+			system_id: &unresolved.system_id,
+			base_uri: self.base_uri.as_deref(),
+		})
+	}
+
+	/// Includes the [unresolved entity](`Expander::unresolved_entity`), if any, as fetched by `resolver`.
+	///
+	/// # Errors
+	///
+	/// Iff `resolver` refuses, or including the entity would exceed a limit.
+	pub fn resolve_with(
+		&mut self,
+		resolver: &mut (impl EntityResolver + ?Sized),
+	) -> Result<(), ExpansionError> {
+		if let Some(unresolved) = self.unresolved.take() {
+			match resolver.resolve(
+				unresolved.public_id.as_deref(),
+				&unresolved.system_id,
+				self.base_uri.as_deref(),
+			) {
+				Ok(source) => {
+					let mut text = source.into_bytes();
+					self.skip_text_declaration(&unresolved.name, &mut text)?;
+					self.include(unresolved.name, text, false)
+				}
+				Err(refusal) => Err(ExpansionError::Refused {
+					name: unresolved.name,
+					refusal,
+				}),
+			}
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Removes the text declaration, if any, from the start of the fetched external parsed entity `text`.
+	///
+	/// Anything else is left to the [`Checker`], including whether the text declaration is followed by content.
+	fn skip_text_declaration(&self, name: &str, text: &mut Vec<u8>) -> Result<(), ExpansionError> {
+		// One byte longer than `text`, so that running out of it isn't mistaken for a clogged buffer.
+		let mut memory = vec![MaybeUninit::uninit(); text.len() + 1];
+		let mut buffer = StrBuf::new(&mut memory);
+		append(&mut buffer, text);
+		let mut scanner = self.checker.scanner().for_external_parsed_entity();
+		let mut in_text_decl = false;
+		loop {
+			match scanner.resume(&mut buffer) {
+				Ok(Ok(Some(Event::TextDeclStart(_)))) => in_text_decl = true,
+				Ok(Ok(Some(Event::TextDeclEnd(_)))) => {
+					let len = text.len() - buffer.filled().len();
+					text.drain(..len);
+					return Ok(());
+				}
+				Ok(Ok(Some(_))) if in_text_decl => (),
+				Ok(Err(error)) if in_text_decl => {
+					return Err(WellFormednessError::from(error).into());
+				}
+				// The entity ends within its text declaration.
+				Err(_) if in_text_decl => {
+					return Err(ExpansionError::UnbalancedEntity(name.into()))
+				}
+				_ => return Ok(()),
+			}
+		}
+	}
+
+	/// Moves what's left of the current entity's text in `expansion_buffer` back into its frame, to be scanned after another entity.
+	fn suspend_frame(&mut self, expansion_buffer: &mut StrBuf) {
+		if let Some(frame) = self.frames.last_mut() {
+			let unscanned = expansion_buffer.filled().len();
+			frame.offset -= unscanned;
+			discard(expansion_buffer, unscanned);
+		}
+	}
+
+	/// Starts scanning `text`, which is either an internal entity's replacement text or a fetched external entity.
+	fn include(
+		&mut self,
+		name: Box<str>,
+		text: Vec<u8>,
+		is_replacement_text: bool,
+	) -> Result<(), ExpansionError> {
+		// The checker doesn't follow references within external entities.
+		if self.frames.iter().any(|frame| frame.name == name) {
+			return Err(WellFormednessError::RecursiveEntityReference(name).into());
+		}
+		if self.frames.len() >= self.depth_limit {
+			return Err(ExpansionError::DepthLimitExceeded);
+		}
+		if is_replacement_text {
+			self.expanded_bytes = self.expanded_bytes.saturating_add(text.len());
+			if self.expanded_bytes > self.expanded_bytes_limit {
+				return Err(ExpansionError::ExpandedBytesLimitExceeded);
+			}
+			if self.expanded_bytes > self.amplification_threshold
+				&& self.expanded_bytes > self.input_bytes.saturating_mul(self.amplification_factor)
+			{
+				return Err(ExpansionError::AmplificationLimitExceeded);
+			}
+		} else {
+			// Fetched text is limited by the resolver instead.
+			self.input_bytes = self.input_bytes.saturating_add(text.len());
+		}
+
+		self.frames.push(Frame {
+			name,
+			text,
+			offset: 0,
+			line_endings_normalized: is_replacement_text,
+			open_elements: self.checker.open_element_count(),
+			markup_depth: self.markup_depth,
+		});
+		Ok(())
+	}
+
+	/// Called once the current entity's replacement text is scanned as far as possible.
+	fn end_entity(&mut self, expansion_buffer: &mut StrBuf) -> Result<(), ExpansionError> {
+		let frame = self.frames.pop().expect("unreachable");
+		let leftover = expansion_buffer.filled();
+		// Only character data that may still continue can be left over.
+		if frame.open_elements != self.checker.open_element_count()
+			|| frame.markup_depth != self.markup_depth
+			|| !leftover
+				.iter()
+				.all(|b| matches!(b, b']' | b' ' | b'\t' | b'\n' | b'\r'))
+		{
+			return Err(ExpansionError::UnbalancedEntity(frame.name));
+		}
+
+		if leftover.is_empty() {
+		} else if let Some(parent) = self.frames.last_mut() {
+			let mut text = leftover.to_vec();
+			text.extend_from_slice(&parent.text[parent.offset..]);
+			parent.text = text;
+			parent.offset = 0;
+			discard(expansion_buffer, expansion_buffer.filled().len());
+		} else {
+			self.bridge = Some(Bridge {
+				leftover: leftover.len(),
+				copied: 0,
+			});
+		}
+		Ok(())
+	}
+}
+
+/// Escapes characters that would otherwise be scanned differently than they are included.
+///
+/// Line breaks in replacement text come from character references, and are left alone as its line endings aren't normalized again.
+/// In attribute values, quotes don't end the value, and whitespace other than spaces goes back to being character references,
+/// so that it's reported as such rather than normalized along with the literal value, like the reference that stood for it.
+fn escape(replacement_text: &str, in_att_value: bool) -> String {
+	let mut escaped = String::with_capacity(replacement_text.len());
+	for c in replacement_text.chars() {
+		match c {
+			'\r' | '\n' | '\t' | '\'' | '"' if in_att_value => {
+				write!(escaped, "&#{};", u32::from(c)).expect("infallible");
+			}
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Copies as much of `data` to the end of `buffer` as fits, returning how much that was.
+fn append(buffer: &mut StrBuf, data: &[u8]) -> usize {
+	let remaining = buffer.remaining_maybe_uninitialized();
+	let len = data.len().min(remaining.len());
+	for (slot, byte) in remaining.iter_mut().zip(&data[..len]) {
+		slot.write(*byte);
+	}
+	unsafe { buffer.assume_filled_n_remaining(len) };
+	len
+}
+
+fn discard(buffer: &mut StrBuf, len: usize) {
+	buffer.shift_filled(len).expect("unreachable");
+}
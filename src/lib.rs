@@ -17,4 +17,7 @@
 mod readme {}
 
 pub mod buffer;
+pub mod expansion;
+pub mod resolver;
 pub mod scanner;
+pub mod well_formedness;
@@ -0,0 +1,232 @@
+//! Sans-IO resolution of external entities.
+//!
+//! Nothing is fetched unless an [`EntityResolver`] that does so is passed to [`Expander::resolve_with`](`crate::expansion::Expander::resolve_with`).
+
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+pub mod catalog;
+
+/// Fetches external entities by their external identifier.
+pub trait EntityResolver {
+	/// Fetches the entity identified by `public_id` and `system_id`,
+	/// where a relative `system_id` is relative to `base_uri`.
+	///
+	/// # Errors
+	///
+	/// Iff the entity isn't fetched.
+	fn resolve(
+		&mut self,
+		public_id: Option<&str>,
+		system_id: &str,
+		base_uri: Option<&str>,
+	) -> Result<EntitySource, Refusal>;
+}
+
+/// The content of a fetched external entity, including any text declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntitySource {
+	/// UTF-8, without byte order mark.
+	Bytes(Vec<u8>),
+	Text(String),
+}
+
+impl EntitySource {
+	#[must_use]
+	pub fn into_bytes(self) -> Vec<u8> {
+		match self {
+			EntitySource::Bytes(bytes) => bytes,
+			EntitySource::Text(text) => text.into_bytes(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+	/// The resolver doesn't fetch this kind of entity.
+	Denied,
+	/// The entity would be fetched from outside of where the resolver may access.
+	OutOfBounds,
+	/// The entity is longer than the resolver's size limit.
+	TooLarge,
+	/// The entity couldn't be fetched.
+	Io(io::ErrorKind),
+}
+
+/// Refuses to fetch any entity. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenyAll;
+
+impl EntityResolver for DenyAll {
+	fn resolve(
+		&mut self,
+		_public_id: Option<&str>,
+		_system_id: &str,
+		_base_uri: Option<&str>,
+	) -> Result<EntitySource, Refusal> {
+		Err(Refusal::Denied)
+	}
+}
+
+/// Reads entities from files in one directory (and its subdirectories).
+///
+/// Only relative system IDs (and base URIs) are resolved, as paths relative to that directory.
+/// `..` segments are refused, as are symbolic links that lead out of the directory
+/// (or any symbolic links, on platforms other than Unix) and anything that isn't a regular file.
+#[derive(Debug, Clone)]
+pub struct FileSystemResolver {
+	root: PathBuf,
+	size_limit: u64,
+}
+
+impl FileSystemResolver {
+	/// Resolves entities within `root`, with a size limit of 1 MiB.
+	///
+	/// # Errors
+	///
+	/// Iff `root` can't be canonicalized.
+	pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+		Ok(Self {
+			root: root.as_ref().canonicalize()?,
+			size_limit: 1 << 20,
+		})
+	}
+
+	/// Sets how long an entity may be, in bytes.
+	#[must_use]
+	pub fn with_size_limit(mut self, size_limit: u64) -> Self {
+		self.size_limit = size_limit;
+		self
+	}
+
+	/// Checks where the file is before opening it, and that the opened file is still the one that was checked.
+	fn open(&self, system_id: &str, base_uri: Option<&str>) -> Result<File, Refusal> {
+		let mut path = self.root.clone();
+		let mut segments = Vec::new();
+		if let Some(base_uri) = base_uri {
+			// Relative to the base's directory.
+			let directory = base_uri
+				.rsplit_once('/')
+				.map_or("", |(directory, _)| directory);
+			relative_segments(base_uri)?;
+			segments.extend(relative_segments(directory)?);
+		}
+		segments.extend(relative_segments(system_id)?);
+		path.extend(segments);
+
+		let io_error = |error: io::Error| Refusal::Io(error.kind());
+		let canonical = path.canonicalize().map_err(io_error)?;
+		if !self.is_in_bounds(&path, &canonical) {
+			return Err(Refusal::OutOfBounds);
+		}
+		let file = open_no_follow(&canonical).map_err(io_error)?;
+		if !file.metadata().map_err(io_error)?.is_file() {
+			return Err(Refusal::Denied);
+		}
+		if is_same_file(&file, &canonical).map_err(io_error)? {
+			Ok(file)
+		} else {
+			Err(Refusal::OutOfBounds)
+		}
+	}
+
+	/// Whether `path`, which canonicalizes to `canonical`, is within the root directory.
+	#[cfg(unix)]
+	fn is_in_bounds(&self, _path: &Path, canonical: &Path) -> bool {
+		canonical.starts_with(&self.root)
+	}
+
+	/// Whether `path`, which canonicalizes to `canonical`, is within the root directory.
+	///
+	/// Without file IDs to tell whether the file was swapped out after this check,
+	/// symbolic links are refused entirely.
+	#[cfg(not(unix))]
+	fn is_in_bounds(&self, path: &Path, canonical: &Path) -> bool {
+		canonical.starts_with(&self.root) && canonical == path
+	}
+}
+
+impl EntityResolver for FileSystemResolver {
+	fn resolve(
+		&mut self,
+		_public_id: Option<&str>,
+		system_id: &str,
+		base_uri: Option<&str>,
+	) -> Result<EntitySource, Refusal> {
+		read_file(self.open(system_id, base_uri)?, self.size_limit).map(EntitySource::Bytes)
+	}
+}
+
+/// Opens `path` for reading, failing if it's a symbolic link and without waiting for a writer if it's a FIFO.
+#[cfg(unix)]
+fn open_no_follow(path: &Path) -> io::Result<File> {
+	use std::os::unix::fs::OpenOptionsExt;
+	OpenOptions::new()
+		.read(true)
+		.custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK)
+		.open(path)
+}
+
+/// Opens `path` for reading.
+#[cfg(not(unix))]
+fn open_no_follow(path: &Path) -> io::Result<File> {
+	OpenOptions::new().read(true).open(path)
+}
+
+/// Whether `file` is the one at `path`, which is canonical.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> io::Result<bool> {
+	use std::os::unix::fs::MetadataExt;
+	let (a, b) = (file.metadata()?, fs::metadata(path)?);
+	Ok((a.dev(), a.ino()) == (b.dev(), b.ino()))
+}
+
+/// Whether `file` is the one at `path`, which is canonical,
+/// as far as that can be told without platform-specific file IDs: `path` still doesn't lead through a symbolic link.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, path: &Path) -> io::Result<bool> {
+	Ok(path.canonicalize()? == path)
+}
+
+/// Reads the file at `path` if it's at most `size_limit` bytes long, without UTF-8 byte order mark.
+fn read(path: &Path, size_limit: u64) -> Result<Vec<u8>, Refusal> {
+	read_file(
+		File::open(path).map_err(|error| Refusal::Io(error.kind()))?,
+		size_limit,
+	)
+}
+
+/// Like [`read`], but for a file that's open already.
+fn read_file(file: File, size_limit: u64) -> Result<Vec<u8>, Refusal> {
+	let mut bytes = Vec::new();
+	file.take(size_limit + 1)
+		.read_to_end(&mut bytes)
+		.map_err(|error| Refusal::Io(error.kind()))?;
+	if bytes.len() as u64 > size_limit {
+		return Err(Refusal::TooLarge);
+	}
+	if bytes.starts_with("\u{FEFF}".as_bytes()) {
+		bytes.drain(..3);
+	}
+	Ok(bytes)
+}
+
+/// Splits a relative URI reference without query or fragment into its path segments, minus `.` and empty ones.
+fn relative_segments(uri: &str) -> Result<Vec<&str>, Refusal> {
+	// Anything that isn't plainly a relative path, including schemes, absolute paths, percent-encoding and backslashes.
+	if uri.starts_with('/') || uri.contains([':', '%', '?', '#', '\\']) {
+		return Err(Refusal::Denied);
+	}
+	let mut segments = Vec::new();
+	for segment in uri.split('/') {
+		match segment {
+			"" | "." => (),
+			".." => return Err(Refusal::OutOfBounds),
+			segment => segments.push(segment),
+		}
+	}
+	Ok(segments)
+}
@@ -0,0 +1,813 @@
+//! Offline resolution through [XML Catalogs](https://www.oasis-open.org/committees/download.php/14809/xml-catalogs.html).
+
+use super::{read, EntityResolver, EntitySource, Refusal};
+use crate::{
+	buffer::StrBuf,
+	expansion::{Expander, ExpansionError, Suspended},
+	scanner::{Event, Scanner},
+	well_formedness::Checker,
+};
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, VecDeque},
+	fmt::Write,
+	io,
+	mem::MaybeUninit,
+	path::{Component, Path, PathBuf},
+	str::from_utf8,
+};
+
+const CATALOG_NAMESPACE: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
+/// The elements read from catalogs. Others are ignored along with their content.
+const CATALOG_ELEMENTS: &[&str] = &[
+	"catalog",
+	"group",
+	"public",
+	"system",
+	"rewriteSystem",
+	"uri",
+	"delegatePublic",
+	"nextCatalog",
+];
+const PUBLICID_URN_PREFIX: &str = "urn:publicid:";
+
+const DEPTH_LIMIT: usize = 64;
+const NAME_BUDGET: usize = 4096;
+const REPLACEMENT_TEXT_BUDGET: usize = 4096;
+const EXPANSION_BUFFER_LEN: usize = 256;
+
+/// Reads entities from the local files that catalogs map their external identifiers to.
+///
+/// The `public`, `system`, `rewriteSystem`, `uri`, `delegatePublic` and `nextCatalog` entries are supported,
+/// and other entries are ignored. Public IDs are normalized and `urn:publicid:` URNs unwrapped.
+///
+/// Only `file:` URIs are read, both for catalogs and entities, so nothing is ever fetched over a network.
+/// Entities that no catalog maps are refused.
+///
+/// Catalogs referenced by `nextCatalog` and `delegatePublic` entries are loaded when first consulted,
+/// and treated as empty if they can't be loaded.
+#[derive(Debug, Clone)]
+pub struct CatalogResolver {
+	/// The URIs of the catalogs that are consulted first, in order.
+	catalogs: Vec<Box<str>>,
+	/// The entries of every catalog loaded so far, or [`None`] where loading failed.
+	loaded: HashMap<Box<str>, Option<Vec<Entry>>>,
+	prefer: Prefer,
+	size_limit: u64,
+	catalog_limit: usize,
+}
+
+/// Which catalog entries match an external identifier with both a public and a system ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefer {
+	/// `public` and `delegatePublic` entries match.
+	Public,
+	/// Only the system ID is matched.
+	System,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CatalogError {
+	/// The catalog couldn't be read.
+	Unreadable(Refusal),
+	/// The catalog isn't well-formed.
+	Malformed(ExpansionError),
+	/// The catalog ends before its document element does.
+	Truncated,
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+	Public {
+		public_id: Box<str>,
+		uri: Box<str>,
+		prefer: Option<Prefer>,
+	},
+	System {
+		system_id: Box<str>,
+		uri: Box<str>,
+	},
+	RewriteSystem {
+		start: Box<str>,
+		prefix: Box<str>,
+	},
+	Uri {
+		name: Box<str>,
+		uri: Box<str>,
+	},
+	DelegatePublic {
+		start: Box<str>,
+		catalog: Box<str>,
+		prefer: Option<Prefer>,
+	},
+	NextCatalog(Box<str>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Query<'a> {
+	ExternalId {
+		public_id: Option<&'a str>,
+		system_id: Option<&'a str>,
+	},
+	Uri(&'a str),
+}
+
+/// What to do after consulting one catalog.
+enum Step {
+	Match(Box<str>),
+	/// Consult only these catalogs, ignoring the system ID.
+	Delegate(Vec<Box<str>>),
+	/// Consult these catalogs before any others.
+	Next(Vec<Box<str>>),
+}
+
+impl Default for CatalogResolver {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl CatalogResolver {
+	/// Creates a resolver without catalogs, with a size limit of 1 MiB, that prefers public IDs
+	/// and consults at most 64 catalogs per resolution.
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			catalogs: Vec::new(),
+			loaded: HashMap::new(),
+			prefer: Prefer::Public,
+			size_limit: 1 << 20,
+			catalog_limit: 64,
+		}
+	}
+
+	/// Sets which entries match where the catalogs don't say so.
+	#[must_use]
+	pub fn with_prefer(mut self, prefer: Prefer) -> Self {
+		self.prefer = prefer;
+		self
+	}
+
+	/// Sets how long a catalog or entity may be, in bytes.
+	#[must_use]
+	pub fn with_size_limit(mut self, size_limit: u64) -> Self {
+		self.size_limit = size_limit;
+		self
+	}
+
+	/// Sets how many catalogs may be consulted to resolve one identifier, which also ends `nextCatalog` loops.
+	#[must_use]
+	pub fn with_catalog_limit(mut self, catalog_limit: usize) -> Self {
+		self.catalog_limit = catalog_limit;
+		self
+	}
+
+	/// Loads the catalog at `path`, to be consulted after those added before.
+	///
+	/// # Errors
+	///
+	/// Iff the catalog can't be loaded, in which case it isn't added.
+	pub fn add_catalog(&mut self, path: impl AsRef<Path>) -> Result<(), CatalogError> {
+		let uri = path
+			.as_ref()
+			.canonicalize()
+			.and_then(|path| {
+				path.to_str()
+					.map(file_uri)
+					.ok_or_else(|| io::ErrorKind::InvalidData.into())
+			})
+			.map_err(|error| CatalogError::Unreadable(Refusal::Io(error.kind())))?;
+		let entries = self.load(&uri)?;
+		self.loaded.insert(uri.clone(), Some(entries));
+		self.catalogs.push(uri);
+		Ok(())
+	}
+
+	/// Looks up the URI that the catalogs map an external identifier to.
+	pub fn resolve_external_id(
+		&mut self,
+		public_id: Option<&str>,
+		system_id: Option<&str>,
+	) -> Option<Box<str>> {
+		let mut public_id = public_id.map(|public_id| {
+			normalize_public_id(&unwrap_urn(public_id).unwrap_or_else(|| public_id.to_owned()))
+		});
+		let mut system_id = system_id.map(normalize_uri);
+		if let Some(unwrapped) = system_id.as_deref().and_then(unwrap_urn) {
+			// If the public ID differs, the spec allows recovering by discarding the system ID, too.
+			public_id.get_or_insert_with(|| normalize_public_id(&unwrapped));
+			system_id = None;
+		}
+		self.lookup(Query::ExternalId {
+			public_id: public_id.as_deref(),
+			system_id: system_id.as_deref(),
+		})
+	}
+
+	/// Looks up the URI that the catalogs map a URI reference to.
+	pub fn resolve_uri(&mut self, uri: &str) -> Option<Box<str>> {
+		if let Some(public_id) = unwrap_urn(uri) {
+			self.resolve_external_id(Some(&public_id), None)
+		} else {
+			self.lookup(Query::Uri(&normalize_uri(uri)))
+		}
+	}
+
+	fn lookup(&mut self, mut query: Query) -> Option<Box<str>> {
+		let mut pending = self.catalogs.iter().cloned().collect::<VecDeque<_>>();
+		let mut consulted = 0;
+		while let Some(catalog) = pending.pop_front() {
+			consulted += 1;
+			if consulted > self.catalog_limit {
+				break;
+			}
+			match self.consult(&catalog, query) {
+				Step::Match(uri) => return Some(uri),
+				Step::Delegate(catalogs) => {
+					pending = catalogs.into();
+					if let Query::ExternalId { system_id, .. } = &mut query {
+						*system_id = None;
+					}
+				}
+				Step::Next(catalogs) => {
+					for catalog in catalogs.into_iter().rev() {
+						pending.push_front(catalog);
+					}
+				}
+			}
+		}
+		None
+	}
+
+	fn consult(&mut self, catalog: &str, query: Query) -> Step {
+		if !self.loaded.contains_key(catalog) {
+			let entries = self.load(catalog).ok();
+			self.loaded.insert(catalog.into(), entries);
+		}
+		let entries = self.loaded[catalog].as_deref().unwrap_or_default();
+
+		let matched = match query {
+			Query::ExternalId {
+				public_id,
+				system_id,
+			} => self.match_external_id(entries, public_id, system_id),
+			Query::Uri(name) => entries.iter().find_map(|entry| match entry {
+				Entry::Uri { name: n, uri } if **n == *name => Some(Step::Match(uri.clone())),
+				_ => None,
+			}),
+		};
+		matched.unwrap_or_else(|| {
+			Step::Next(
+				entries
+					.iter()
+					.filter_map(|entry| match entry {
+						Entry::NextCatalog(catalog) => Some(catalog.clone()),
+						_ => None,
+					})
+					.collect(),
+			)
+		})
+	}
+
+	fn match_external_id(
+		&self,
+		entries: &[Entry],
+		public_id: Option<&str>,
+		system_id: Option<&str>,
+	) -> Option<Step> {
+		if let Some(system_id) = system_id {
+			let mut rewrite: Option<(&str, &str)> = None;
+			for entry in entries {
+				match entry {
+					Entry::System { system_id: s, uri } if **s == *system_id => {
+						return Some(Step::Match(uri.clone()))
+					}
+					Entry::RewriteSystem { start, prefix }
+						if system_id.starts_with(&**start)
+							&& match rewrite {
+								Some((longest, _)) => start.len() > longest.len(),
+								None => true,
+							} =>
+					{
+						rewrite = Some((start, prefix));
+					}
+					_ => (),
+				}
+			}
+			if let Some((start, prefix)) = rewrite {
+				return rewrite_uri(prefix, &system_id[start.len()..]).map(Step::Match);
+			}
+		}
+
+		if let Some(public_id) = public_id {
+			// With a system ID, public entries only match where public IDs are preferred.
+			let considered = |prefer: &Option<Prefer>| {
+				system_id.is_none() || prefer.unwrap_or(self.prefer) == Prefer::Public
+			};
+			let mut delegates = Vec::new();
+			for entry in entries {
+				match entry {
+					Entry::Public {
+						public_id: p,
+						uri,
+						prefer,
+					} if **p == *public_id && considered(prefer) => return Some(Step::Match(uri.clone())),
+					Entry::DelegatePublic {
+						start,
+						catalog,
+						prefer,
+					} if public_id.starts_with(&**start) && considered(prefer) => {
+						delegates.push((start.len(), catalog.clone()));
+					}
+					_ => (),
+				}
+			}
+			if !delegates.is_empty() {
+				// Longest match first, keeping the order of entries otherwise.
+				delegates.sort_by_key(|&(len, _)| Reverse(len));
+				let mut catalogs: Vec<Box<str>> = Vec::new();
+				for (_, catalog) in delegates {
+					if !catalogs.contains(&catalog) {
+						catalogs.push(catalog);
+					}
+				}
+				return Some(Step::Delegate(catalogs));
+			}
+		}
+		None
+	}
+
+	/// Reads and scans the catalog at `uri`.
+	fn load(&self, uri: &str) -> Result<Vec<Entry>, CatalogError> {
+		let bytes = file_path(uri)
+			.and_then(|path| read(&path, self.size_limit))
+			.map_err(CatalogError::Unreadable)?;
+		let len = bytes.len();
+		let mut buffer = bytes.into_iter().map(MaybeUninit::new).collect::<Vec<_>>();
+		let mut buffer = StrBuf::new(&mut buffer);
+		unsafe {
+			//SAFETY: The whole buffer was initialized above.
+			buffer.assume_filled_n_remaining(len);
+		}
+		let mut expansion_buffer = [MaybeUninit::uninit(); EXPANSION_BUFFER_LEN];
+		let mut expansion_buffer = StrBuf::new(&mut expansion_buffer);
+		let mut expander = Expander::new(
+			Checker::new(
+				Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+				NAME_BUDGET,
+			),
+			REPLACEMENT_TEXT_BUDGET,
+		);
+
+		let mut reader = Reader::new(uri);
+		let mut stalled = false;
+		loop {
+			match expander.resume(&mut buffer, &mut expansion_buffer) {
+				Ok(Ok(Some(event))) => {
+					stalled = false;
+					reader.observe(&event);
+				}
+				Ok(Ok(None)) => break,
+				Ok(Err(error)) => return Err(CatalogError::Malformed(error)),
+				// External entities are skipped.
+				Err(Suspended::ResolveEntity) => (),
+				// The rest of the catalog is already in `buffer`, so only the expansion buffer can make progress.
+				Err(Suspended::MoreInputRequired) if stalled => break,
+				Err(Suspended::MoreInputRequired) => unsafe {
+					//SAFETY: No events are held across resumes.
+					stalled = true;
+					buffer.unshift_reset();
+					expansion_buffer.unshift_reset();
+				},
+			}
+		}
+		reader.finish()
+	}
+}
+
+impl EntityResolver for CatalogResolver {
+	fn resolve(
+		&mut self,
+		public_id: Option<&str>,
+		system_id: &str,
+		_base_uri: Option<&str>,
+	) -> Result<EntitySource, Refusal> {
+		let uri = self
+			.resolve_external_id(public_id, Some(system_id))
+			.ok_or(Refusal::Denied)?;
+		read(&file_path(&uri)?, self.size_limit).map(EntitySource::Bytes)
+	}
+}
+
+/// Collects a catalog's entries from its [`Event`]s.
+struct Reader {
+	entries: Vec<Entry>,
+	/// One per open element, innermost last.
+	scopes: Vec<Scope>,
+	collecting: Collecting,
+	name: String,
+	attributes: Vec<(String, String)>,
+	ended: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Collecting {
+	Nothing,
+	ElementName,
+	AttributeName,
+	AttValue,
+}
+
+struct Scope {
+	/// Namespace declarations as prefix and name.
+	namespaces: Vec<(Box<str>, Box<str>)>,
+	base: Box<str>,
+	prefer: Option<Prefer>,
+	/// Whether this element and its content don't belong to the catalog.
+	ignored: bool,
+}
+
+impl Reader {
+	fn new(uri: &str) -> Self {
+		Self {
+			entries: Vec::new(),
+			scopes: vec![Scope {
+				namespaces: Vec::new(),
+				base: uri.into(),
+				prefer: None,
+				ignored: false,
+			}],
+			collecting: Collecting::Nothing,
+			name: String::new(),
+			attributes: Vec::new(),
+			ended: false,
+		}
+	}
+
+	fn observe(&mut self, event: &Event) {
+		self.collecting = match event {
+			Event::StartTagStart(_) => {
+				self.name.clear();
+				self.attributes.clear();
+				Collecting::ElementName
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::ElementName => {
+				self.name.push_str(chunk);
+				Collecting::ElementName
+			}
+			Event::AttributeNameChunk(chunk) => {
+				if self.collecting != Collecting::AttributeName {
+					self.attributes.push(Default::default());
+				}
+				if let Some((name, _)) = self.attributes.last_mut() {
+					name.push_str(chunk);
+				}
+				Collecting::AttributeName
+			}
+			Event::AttValueStart(_) => Collecting::AttValue,
+			Event::AttValueChunk(chunk) if self.collecting == Collecting::AttValue => {
+				if let Some((_, value)) = self.attributes.last_mut() {
+					value.push_str(chunk);
+				}
+				Collecting::AttValue
+			}
+			Event::CharRefEnd(_, c) if self.collecting == Collecting::AttValue => {
+				if let Some((_, value)) = self.attributes.last_mut() {
+					value.push(*c);
+				}
+				Collecting::AttValue
+			}
+			Event::StartTagEnd(_) => {
+				self.start_element();
+				Collecting::Nothing
+			}
+			Event::StartTagEndEmpty(_) => {
+				self.start_element();
+				self.end_element();
+				Collecting::Nothing
+			}
+			Event::EndTagEnd(_) => {
+				self.end_element();
+				Collecting::Nothing
+			}
+			// Names of references in attribute values, for example.
+			_ => self.collecting,
+		}
+	}
+
+	fn attribute(&self, name: &str) -> Option<&str> {
+		self.attributes
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	fn start_element(&mut self) {
+		let parent = self.scopes.last().expect("unreachable");
+		let mut scope = Scope {
+			namespaces: Vec::new(),
+			base: parent.base.clone(),
+			prefer: parent.prefer,
+			ignored: parent.ignored,
+		};
+		for (name, value) in &self.attributes {
+			if name == "xmlns" {
+				scope.namespaces.push(("".into(), value.as_str().into()));
+			} else if let Some(prefix) = name.strip_prefix("xmlns:") {
+				scope
+					.namespaces
+					.push((prefix.into(), value.as_str().into()));
+			}
+		}
+		if let Some(base) = self.attribute("xml:base") {
+			scope.base = resolve_reference(&scope.base, &normalize_uri(base)).into();
+		}
+		match self.attribute("prefer") {
+			Some("public") => scope.prefer = Some(Prefer::Public),
+			Some("system") => scope.prefer = Some(Prefer::System),
+			_ => (),
+		}
+		self.scopes.push(scope);
+
+		let (prefix, local_name) = self.name.split_once(':').unwrap_or(("", &self.name));
+		let namespace = self
+			.scopes
+			.iter()
+			.rev()
+			.flat_map(|scope| scope.namespaces.iter().rev())
+			.find(|(p, _)| **p == *prefix)
+			.map(|(_, namespace)| &**namespace);
+		if namespace != Some(CATALOG_NAMESPACE) || !CATALOG_ELEMENTS.contains(&local_name) {
+			self.scopes.last_mut().expect("unreachable").ignored = true;
+		} else if !self.scopes.last().expect("unreachable").ignored {
+			if let Some(entry) = self.entry(local_name) {
+				self.entries.push(entry);
+			}
+		}
+	}
+
+	/// The entry for the current element, if it's a complete one.
+	fn entry(&self, local_name: &str) -> Option<Entry> {
+		let scope = self.scopes.last().expect("unreachable");
+		let uri = |name| {
+			self.attribute(name)
+				.map(|uri| Box::from(resolve_reference(&scope.base, &normalize_uri(uri))))
+		};
+		let public_id = |name| {
+			self.attribute(name)
+				.map(|id| normalize_public_id(id).into())
+		};
+		let system_id = |name| self.attribute(name).map(|id| normalize_uri(id).into());
+
+		match local_name {
+			"public" => public_id("publicId")
+				.zip(uri("uri"))
+				.map(|(public_id, uri)| Entry::Public {
+					public_id,
+					uri,
+					prefer: scope.prefer,
+				}),
+			"system" => system_id("systemId")
+				.zip(uri("uri"))
+				.map(|(system_id, uri)| Entry::System { system_id, uri }),
+			"rewriteSystem" => system_id("systemIdStartString")
+				.zip(uri("rewritePrefix"))
+				.map(|(start, prefix)| Entry::RewriteSystem { start, prefix }),
+			"uri" => system_id("name")
+				.zip(uri("uri"))
+				.map(|(name, uri)| Entry::Uri { name, uri }),
+			"delegatePublic" => {
+				public_id("publicIdStartString")
+					.zip(uri("catalog"))
+					.map(|(start, catalog)| Entry::DelegatePublic {
+						start,
+						catalog,
+						prefer: scope.prefer,
+					})
+			}
+			"nextCatalog" => uri("catalog").map(Entry::NextCatalog),
+			_ => None,
+		}
+	}
+
+	fn end_element(&mut self) {
+		self.scopes.pop();
+		if self.scopes.len() == 1 {
+			self.ended = true;
+		}
+	}
+
+	fn finish(self) -> Result<Vec<Entry>, CatalogError> {
+		if self.ended {
+			Ok(self.entries)
+		} else {
+			Err(CatalogError::Truncated)
+		}
+	}
+}
+
+/// Collapses whitespace, as per [6.2 Public Identifier Normalization](https://www.oasis-open.org/committees/download.php/14809/xml-catalogs.html#s.public.norm).
+fn normalize_public_id(public_id: &str) -> String {
+	public_id
+		.split([' ', '\t', '\r', '\n'])
+		.filter(|part| !part.is_empty())
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Percent-encodes the characters that URIs can't contain, as per [6.3 System Identifier and URI Normalization](https://www.oasis-open.org/committees/download.php/14809/xml-catalogs.html#s.sysid.norm).
+fn normalize_uri(uri: &str) -> String {
+	let mut normalized = String::with_capacity(uri.len());
+	for c in uri.chars() {
+		if c.is_ascii_graphic()
+			&& !matches!(c, '"' | '<' | '>' | '\\' | '^' | '`' | '{' | '|' | '}')
+		{
+			normalized.push(c);
+		} else {
+			for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+				write!(normalized, "%{byte:02X}").expect("infallible");
+			}
+		}
+	}
+	normalized
+}
+
+/// The public ID in a `urn:publicid:` URN, as per [6.4 URN "Unwrapping"](https://www.oasis-open.org/committees/download.php/14809/xml-catalogs.html#s.urnpublicid).
+fn unwrap_urn(urn: &str) -> Option<String> {
+	if !urn
+		.get(..PUBLICID_URN_PREFIX.len())?
+		.eq_ignore_ascii_case(PUBLICID_URN_PREFIX)
+	{
+		return None;
+	}
+	let mut rest = &urn[PUBLICID_URN_PREFIX.len()..];
+	let mut public_id = String::new();
+	while let Some(c) = rest.chars().next() {
+		let escaped = rest.get(1..3).filter(|_| c == '%').and_then(|hex| {
+			match hex.to_ascii_uppercase().as_str() {
+				"2B" => Some('+'),
+				"3A" => Some(':'),
+				"2F" => Some('/'),
+				"3B" => Some(';'),
+				"27" => Some('\''),
+				"3F" => Some('?'),
+				"23" => Some('#'),
+				"25" => Some('%'),
+				_ => None,
+			}
+		});
+		if let Some(escaped) = escaped {
+			public_id.push(escaped);
+			rest = &rest[3..];
+		} else {
+			match c {
+				'+' => public_id.push(' '),
+				':' => public_id.push_str("//"),
+				';' => public_id.push_str("::"),
+				c => public_id.push(c),
+			}
+			rest = &rest[c.len_utf8()..];
+		}
+	}
+	Some(public_id)
+}
+
+/// Resolves `reference` against the absolute URI `base`, as per [RFC 3986 5.2](https://www.rfc-editor.org/rfc/rfc3986#section-5.2).
+fn resolve_reference(base: &str, reference: &str) -> String {
+	if scheme_len(reference).is_some() {
+		return reference.to_owned();
+	}
+	let base = &base[..base.find(['?', '#']).unwrap_or(base.len())];
+	let scheme_len = scheme_len(base).unwrap_or(0);
+	if reference.starts_with("//") {
+		return format!("{}{}", &base[..scheme_len], reference);
+	}
+	let origin_len = match base[scheme_len..].strip_prefix("//") {
+		Some(authority) => scheme_len + 2 + authority.find('/').unwrap_or(authority.len()),
+		None => scheme_len,
+	};
+	let (origin, base_path) = base.split_at(origin_len);
+
+	let (path, rest) = reference.split_at(reference.find(['?', '#']).unwrap_or(reference.len()));
+	let path = if path.starts_with('/') {
+		remove_dot_segments(path)
+	} else if path.is_empty() && rest.is_empty() {
+		return base.to_owned();
+	} else if path.is_empty() {
+		base_path.to_owned()
+	} else {
+		let directory = base_path
+			.rsplit_once('/')
+			.map_or("", |(directory, _)| directory);
+		remove_dot_segments(&format!("{directory}/{path}"))
+	};
+	format!("{origin}{path}{rest}")
+}
+
+/// The length of `uri`'s scheme, including the `:`, if it has one.
+fn scheme_len(uri: &str) -> Option<usize> {
+	let len = uri.find(':')?;
+	let mut scheme = uri[..len].chars();
+	(scheme.next()?.is_ascii_alphabetic()
+		&& scheme.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+	.then_some(len + 1)
+}
+
+fn remove_dot_segments(path: &str) -> String {
+	let mut segments = Vec::new();
+	for segment in path.split('/').skip(1) {
+		match segment {
+			"." => (),
+			".." => {
+				segments.pop();
+			}
+			segment => segments.push(segment),
+		}
+	}
+	if path.ends_with("/.") || path.ends_with("/..") {
+		segments.push("");
+	}
+	format!("/{}", segments.join("/"))
+}
+
+/// Replaces a matched start string with `prefix`, unless `suffix` would lead out of the prefix's directory.
+fn rewrite_uri(prefix: &str, suffix: &str) -> Option<Box<str>> {
+	let decoded = percent_decode(suffix)?;
+	if decoded.starts_with('/')
+		|| decoded.contains('\\')
+		|| scheme_len(&decoded).is_some()
+		|| decoded
+			.split(['/', '?', '#'])
+			.any(|segment| segment == "..")
+	{
+		return None;
+	}
+	let (directory, name) = prefix.split_at(prefix.rfind('/').map_or(0, |i| i + 1));
+	let uri = resolve_reference(prefix, &format!("{name}{suffix}"));
+	uri.starts_with(directory).then(|| uri.into())
+}
+
+/// The `file:` URI of the absolute `path`.
+fn file_uri(path: &str) -> Box<str> {
+	let mut uri = String::from("file://");
+	for c in path.chars() {
+		if c.is_ascii_alphanumeric() || "/-._~!$&'()*+,;=:@".contains(c) {
+			uri.push(c);
+		} else {
+			for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+				write!(uri, "%{byte:02X}").expect("infallible");
+			}
+		}
+	}
+	uri.into()
+}
+
+/// The local path of a `file:` URI.
+fn file_path(uri: &str) -> Result<PathBuf, Refusal> {
+	if !uri
+		.get(..5)
+		.is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+	{
+		return Err(Refusal::Denied);
+	}
+	let mut path = &uri[5..];
+	if let Some(authority_and_path) = path.strip_prefix("//") {
+		let (authority, rest) = authority_and_path.split_at(
+			authority_and_path
+				.find('/')
+				.unwrap_or(authority_and_path.len()),
+		);
+		if !(authority.is_empty() || authority.eq_ignore_ascii_case("localhost")) {
+			return Err(Refusal::Denied);
+		}
+		path = rest;
+	}
+	if !path.starts_with('/') || path.contains(['?', '#']) {
+		return Err(Refusal::Denied);
+	}
+
+	percent_decode(path)
+		.map(PathBuf::from)
+		.filter(|path| !path.components().any(|c| c == Component::ParentDir))
+		.ok_or(Refusal::Denied)
+}
+
+/// Decodes the percent-encoded octets in `text`, if they form UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+	let mut bytes = Vec::with_capacity(text.len());
+	let mut rest = text.as_bytes();
+	while let [byte, tail @ ..] = rest {
+		let decoded = tail
+			.get(..2)
+			.filter(|hex| *byte == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+			.and_then(|hex| u8::from_str_radix(from_utf8(hex).ok()?, 16).ok());
+		if let Some(decoded) = decoded {
+			bytes.push(decoded);
+			rest = &tail[2..];
+		} else {
+			bytes.push(*byte);
+			rest = tail;
+		}
+	}
+	String::from_utf8(bytes).ok()
+}
@@ -1,8 +1,8 @@
 use crate::{
 	buffer::{Indeterminate, StrBuf, Utf8Error},
-	scanner::xml1_0::{Grammar, Xml1_0},
+	scanner::xml1_0::{Grammar, Xml1_0, Xml1_0ExtSubset},
 };
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 use tap::Pipe;
 use tracing::{instrument, trace};
 
@@ -23,7 +23,7 @@ enum Next<'a> {
 #[allow(clippy::enum_glob_use)]
 use Next::*;
 
-use self::xml1_1::Xml1_1;
+use self::xml1_1::{Xml1_1, Xml1_1ExtSubset};
 impl Debug for Next<'_> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -65,6 +65,14 @@ enum RetVal {
 
 pub struct Scanner {
 	depth_limit: usize,
+	drop_ignored_content: bool,
+	version_policy: VersionPolicy,
+	literal_attribute_value_normalization: bool,
+	attribute_types: AttributeTypes,
+	char_ref_radix: u32,
+	/// The value of the character reference being scanned, or [`None`] once it's out of range.
+	char_ref_value: Option<u32>,
+	version: Option<XmlVersion>,
 	states: Vec<u8>,
 	call_stack: Vec<NextFn>,
 }
@@ -72,15 +80,60 @@ impl Debug for Scanner {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("Scanner")
 			.field("depth_limit", &self.depth_limit)
+			.field("drop_ignored_content", &self.drop_ignored_content)
+			.field("version_policy", &self.version_policy)
+			.field(
+				"literal_attribute_value_normalization",
+				&self.literal_attribute_value_normalization,
+			)
+			.field("attribute_types", &self.attribute_types)
+			.field("char_ref_radix", &self.char_ref_radix)
+			.field("char_ref_value", &self.char_ref_value)
+			.field("version", &self.version)
 			.field("states", &self.states)
 			// .field("call_stack", &self.call_stack)
-			.finish()
+			.finish_non_exhaustive()
+	}
+}
+
+/// The XML version (and with that the set of grammar rules) a document is scanned as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmlVersion {
+	Xml1_0,
+	Xml1_1,
+}
+
+/// Which XML versions a [`Scanner`] accepts for a document, as per its XML declaration.
+///
+/// This doesn't apply to the text declarations of external entities, which are scanned as the version they're created for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionPolicy {
+	/// Only `1.0`, which is also implied by a missing XML declaration.
+	Xml1_0,
+	/// Only `1.1`, so documents without XML declaration are rejected too.
+	Xml1_1,
+	/// Exactly `1.0` or `1.1`.
+	Both,
+	/// Any `1.x`, with versions other than `1.1` processed as XML 1.0,
+	/// as per the fifth edition of XML 1.0.
+	#[default]
+	Lenient,
+}
+
+/// Whether `c` can go in a name, at its start or later on.
+pub(crate) fn is_name_char(c: char, at_start: bool) -> bool {
+	if at_start {
+		Xml1_0::test_NameStartChar(c)
+	} else {
+		Xml1_0::test_NameChar(c)
 	}
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ScannerError {
 	DepthLimitExceeded,
+	/// The names kept for attribute-value normalization are longer than the attribute type budget in bytes.
+	AttributeTypeBudgetExceeded,
 	XmlError(Error),
 	BufferClogged,
 }
@@ -101,15 +154,130 @@ impl From<Indeterminate> for MoreInputRequired {
 }
 
 impl Scanner {
+	/// Creates a scanner for a document entity.
 	#[must_use]
 	pub fn new(depth_limit: usize) -> Self {
+		Self::new_at(depth_limit, None, Xml1_1::document)
+	}
+
+	/// Creates a scanner for an external DTD subset referenced by a document of the given `version`.
+	///
+	/// Parameter entity references are reported, but not expanded.
+	/// This includes those standing in for a conditional section's keyword,
+	/// so the caller must feed the referenced replacement text right after the [`Event::PEReferenceEnd`].
+	#[must_use]
+	pub fn new_external_subset(depth_limit: usize, version: XmlVersion) -> Self {
+		Self::new_at(
+			depth_limit,
+			Some(version),
+			match version {
+				XmlVersion::Xml1_0 => Xml1_0ExtSubset::extSubset,
+				XmlVersion::Xml1_1 => Xml1_1ExtSubset::extSubset,
+			},
+		)
+	}
+
+	/// Creates a scanner for an external parsed entity referenced by a document of the given `version`.
+	///
+	/// The entity is scanned as part of that document, regardless of the version in its text declaration.
+	#[must_use]
+	pub fn new_external_parsed_entity(depth_limit: usize, version: XmlVersion) -> Self {
+		Self::new_at(
+			depth_limit,
+			Some(version),
+			match version {
+				XmlVersion::Xml1_0 => Xml1_0::extParsedEnt,
+				XmlVersion::Xml1_1 => Xml1_1::extParsedEnt,
+			},
+		)
+	}
+
+	/// Creates a scanner for an external parsed entity referenced in what this one scans, with the same depth limit.
+	///
+	/// The document is assumed to be XML 1.0 if its version isn't known yet.
+	pub(crate) fn for_external_parsed_entity(&self) -> Self {
+		Self::new_external_parsed_entity(
+			self.depth_limit,
+			self.version.unwrap_or(XmlVersion::Xml1_0),
+		)
+	}
+
+	fn new_at(depth_limit: usize, version: Option<XmlVersion>, entry: NextFn) -> Self {
 		Self {
 			depth_limit,
+			drop_ignored_content: false,
+			version_policy: VersionPolicy::default(),
+			literal_attribute_value_normalization: false,
+			attribute_types: AttributeTypes::new(1 << 20),
+			char_ref_radix: 10,
+			char_ref_value: Some(0),
+			version,
 			states: vec![0],
-			call_stack: vec![Xml1_1::document],
+			call_stack: vec![entry],
 		}
 	}
 
+	/// Sets whether the content of ignored conditional sections is dropped
+	/// instead of being reported as [`Event::IgnoreSectChunk`]s.
+	///
+	/// Defaults to `false`.
+	#[must_use]
+	pub fn with_drop_ignored_content(mut self, drop_ignored_content: bool) -> Self {
+		self.drop_ignored_content = drop_ignored_content;
+		self
+	}
+
+	/// Sets whether whitespace written literally in [`Event::AttValueChunk`]s is normalized.
+	///
+	/// Whitespace is replaced with spaces.
+	/// Values of attributes that were first declared with a type other than `CDATA` in an attribute-list declaration scanned by this [`Scanner`]
+	/// also have leading and trailing spaces removed and runs of spaces collapsed into one, as do those declarations' default values.
+	///
+	/// This is only the part of [3.3.3 Attribute-Value Normalization](https://www.w3.org/TR/xml11/#AVNormalize) that doesn't depend on references.
+	/// They're still reported as such, and count as content between runs of spaces,
+	/// so spaces they stand for, like `&#32;`, aren't trimmed or collapsed with neighbouring ones.
+	/// Whatever replaces references has to do that for tokenized values.
+	///
+	/// Defaults to `false`.
+	#[must_use]
+	pub fn with_literal_attribute_value_normalization(
+		mut self,
+		literal_attribute_value_normalization: bool,
+	) -> Self {
+		self.literal_attribute_value_normalization = literal_attribute_value_normalization;
+		self
+	}
+
+	/// Sets how many bytes of element type and attribute names are kept to tell which attribute values are tokenized,
+	/// with [literal attribute-value normalization](`Scanner::with_literal_attribute_value_normalization`).
+	///
+	/// Scanning fails with [`ScannerError::AttributeTypeBudgetExceeded`] once attribute-list declarations would take up more.
+	///
+	/// Defaults to 1 MiB.
+	#[must_use]
+	pub fn with_attribute_type_budget(mut self, attribute_type_budget: usize) -> Self {
+		self.attribute_types.budget = attribute_type_budget;
+		self
+	}
+
+	/// Sets which XML versions are accepted.
+	///
+	/// Defaults to [`VersionPolicy::Lenient`].
+	#[must_use]
+	pub fn with_version_policy(mut self, version_policy: VersionPolicy) -> Self {
+		self.version_policy = version_policy;
+		self
+	}
+
+	/// The XML version the document is being scanned as.
+	///
+	/// This is [`None`] until the version number in the XML declaration has been scanned or the declaration was found to be missing,
+	/// and takes into account that documents with other versions than 1.1 are processed as XML 1.0.
+	#[must_use]
+	pub fn version(&self) -> Option<XmlVersion> {
+		self.version
+	}
+
 	//ON STREAM: Return an error if the buffer is clogged!
 	#[instrument(ret(Debug))]
 	pub fn resume<'a>(
@@ -182,9 +350,66 @@ impl Scanner {
 				Yield(state, internal_event) => {
 					*self.states.last_mut().expect("unreachable") = state;
 					match internal_event {
-						Event_::Public(event) => return Ok(Ok(Some(event))),
+						Event_::Public(Event::IgnoreSectChunk(_)) if self.drop_ignored_content => {}
+						Event_::Public(event) => {
+							self.decode_char_ref(&event);
+							if self.literal_attribute_value_normalization {
+								if let Err(error) = self.attribute_types.observe(&event) {
+									break Err(error);
+								}
+							}
+							return Ok(Ok(Some(event)));
+						}
+						Event_::CharRefEnd(end, is_legal) => {
+							match self.char_ref_value.and_then(char::from_u32) {
+								Some(c) if is_legal(c) => {
+									return Ok(Ok(Some(Event::CharRefEnd(end, c))));
+								}
+								_ => break Err(ScannerError::XmlError(Error::IllegalCharRef)),
+							}
+						}
+						Event_::AttValueStart(quote) => {
+							if self.literal_attribute_value_normalization {
+								*self.states.last_mut().expect("unreachable") +=
+									if self.attribute_types.is_tokenized() {
+										xml1_0::ATT_VALUE_TOKENIZED_NORMALIZATION
+									} else {
+										xml1_0::ATT_VALUE_CDATA_NORMALIZATION
+									};
+							}
+							return Ok(Ok(Some(Event::AttValueStart(quote))));
+						}
+						Event_::ConfirmVersion1_1 => {
+							trace!("Confirmed XML 1.1.");
+							if self.version_policy == VersionPolicy::Xml1_0 {
+								break Err(ScannerError::XmlError(Error::VersionNotAllowed(
+									self.version_policy,
+								)));
+							}
+							self.version = Some(XmlVersion::Xml1_1);
+						}
+						Event_::ConfirmVersion1_0 => {
+							if self.version_policy == VersionPolicy::Xml1_1 {
+								break Err(ScannerError::XmlError(Error::VersionNotAllowed(
+									self.version_policy,
+								)));
+							}
+						}
+						Event_::ConfirmOtherVersion => {
+							if self.version_policy != VersionPolicy::Lenient {
+								break Err(ScannerError::XmlError(Error::VersionNotAllowed(
+									self.version_policy,
+								)));
+							}
+						}
 						Event_::RebootToVersion1_0 => {
 							trace!("Rebooting to XML 1.0.");
+							if self.version_policy == VersionPolicy::Xml1_1 {
+								break Err(ScannerError::XmlError(Error::VersionNotAllowed(
+									self.version_policy,
+								)));
+							}
+							self.version = Some(XmlVersion::Xml1_0);
 							self.states.clear();
 							self.call_stack.clear();
 
@@ -193,16 +418,22 @@ impl Scanner {
 						}
 						Event_::DowngradeFrom1_1SingleQuoted => {
 							trace!("Downgrading into XML 1.0 (single-quoted version).");
+							self.version = Some(XmlVersion::Xml1_0);
+							self.states.clear();
+							self.call_stack.clear();
 
-							// States are analogous.
+							// States are analogous, so the XML 1.0 grammar recreates the current stack.
 							self.states
 								.push(xml1_0::START_AT_VERSION_NUMBER_SINGLE_QUOTE);
 							self.call_stack.push(Xml1_0::document);
 						}
 						Event_::DowngradeFrom1_1DoubleQuoted => {
 							trace!("Downgrading into XML 1.0 (double-quoted version).");
+							self.version = Some(XmlVersion::Xml1_0);
+							self.states.clear();
+							self.call_stack.clear();
 
-							// States are analogous.
+							// States are analogous, so the XML 1.0 grammar recreates the current stack.
 							self.states
 								.push(xml1_0::START_AT_VERSION_NUMBER_DOUBLE_QUOTE);
 							self.call_stack.push(Xml1_0::document);
@@ -215,14 +446,157 @@ impl Scanner {
 		}
 		.pipe(Ok)
 	}
+
+	/// Accumulates the value of a character reference as its digits are scanned.
+	fn decode_char_ref(&mut self, event: &Event) {
+		match event {
+			Event::CharRefDecimalStart(_) => {
+				self.char_ref_radix = 10;
+				self.char_ref_value = Some(0);
+			}
+			Event::CharRefHexadecimalStart(_) => {
+				self.char_ref_radix = 16;
+				self.char_ref_value = Some(0);
+			}
+			Event::CharRefDecimalChunk(digits) | Event::CharRefHexadecimalChunk(digits) => {
+				for &digit in digits.iter() {
+					let digit = char::from(digit)
+						.to_digit(self.char_ref_radix)
+						.expect("unreachable");
+					self.char_ref_value = self
+						.char_ref_value
+						.and_then(|value| {
+							value.checked_mul(self.char_ref_radix)?.checked_add(digit)
+						})
+						.filter(|&value| value <= u32::from(char::MAX));
+				}
+			}
+			_ => (),
+		}
+	}
+}
+
+/// Tracks attribute-list declarations and which attribute a value belongs to, for attribute-value normalization.
+#[derive(Debug)]
+struct AttributeTypes {
+	/// Whether each declared attribute is tokenized, by element type and attribute name.
+	tokenized: HashMap<String, HashMap<String, bool>>,
+	/// The length in bytes of the names kept in [`tokenized`](`AttributeTypes::tokenized`).
+	used: usize,
+	budget: usize,
+	element: String,
+	attribute: String,
+	collecting: Collecting,
+	in_attlist_decl: bool,
+	/// Whether the attribute currently being declared is tokenized, for its default value.
+	declaring_tokenized: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Collecting {
+	#[default]
+	Nothing,
+	Element,
+	Attribute,
+}
+
+impl AttributeTypes {
+	fn new(budget: usize) -> Self {
+		Self {
+			tokenized: HashMap::new(),
+			used: 0,
+			budget,
+			element: String::new(),
+			attribute: String::new(),
+			collecting: Collecting::Nothing,
+			in_attlist_decl: false,
+			declaring_tokenized: false,
+		}
+	}
+
+	fn observe(&mut self, event: &Event) -> Result<(), ScannerError> {
+		self.collecting = match event {
+			Event::StartTagStart(_) | Event::AttlistDeclStart(_) => {
+				self.element.clear();
+				self.attribute.clear();
+				self.in_attlist_decl = matches!(event, Event::AttlistDeclStart(_));
+				Collecting::Element
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::Element => {
+				self.element.push_str(chunk);
+				Collecting::Element
+			}
+			Event::AttributeNameChunk(chunk) => {
+				if self.collecting != Collecting::Attribute {
+					self.attribute.clear();
+				}
+				self.attribute.push_str(chunk);
+				Collecting::Attribute
+			}
+			Event::StringType(_) if self.collecting == Collecting::Attribute => {
+				self.declare(false);
+				Collecting::Nothing
+			}
+			Event::TokenizedType(_) | Event::NOTATION(_) | Event::GroupStart(_)
+				if self.collecting == Collecting::Attribute =>
+			{
+				self.declare(true);
+				Collecting::Nothing
+			}
+			_ => Collecting::Nothing,
+		};
+		// The names being collected count too, so that they can't grow without bound either.
+		if self.collecting != Collecting::Nothing
+			&& self.used + self.element.len() + self.attribute.len() > self.budget
+		{
+			Err(ScannerError::AttributeTypeBudgetExceeded)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Only the first declaration of an attribute is binding.
+	fn declare(&mut self, tokenized: bool) {
+		self.declaring_tokenized = tokenized;
+		if !self.tokenized.contains_key(&self.element) {
+			self.used += self.element.len();
+		}
+		let attributes = self.tokenized.entry(self.element.clone()).or_default();
+		if !attributes.contains_key(&self.attribute) {
+			self.used += self.attribute.len();
+			attributes.insert(self.attribute.clone(), tokenized);
+		}
+	}
+
+	/// Whether the attribute value that's starting is that of a tokenized attribute (or default thereof).
+	fn is_tokenized(&self) -> bool {
+		if self.in_attlist_decl {
+			self.declaring_tokenized
+		} else {
+			self.tokenized
+				.get(&self.element)
+				.and_then(|attributes| attributes.get(&self.attribute))
+				.copied()
+				.unwrap_or(false)
+		}
+	}
 }
 
 #[derive(Debug)]
 enum Event_<'a> {
 	Public(Event<'a>),
+	ConfirmVersion1_1,
+	/// The version number was exactly `1.0`, after downgrading.
+	ConfirmVersion1_0,
+	/// The version number was `1.` followed by digits other than exactly `0` or `1`, after downgrading.
+	ConfirmOtherVersion,
 	RebootToVersion1_0,
 	DowngradeFrom1_1SingleQuoted,
 	DowngradeFrom1_1DoubleQuoted,
+	/// Reported as [`Event::CharRefEnd`] iff the reference is to a character that passes the given `Char` test.
+	CharRefEnd(&'a mut [u8; 1], fn(char) -> bool),
+	/// Reported as [`Event::AttValueStart`], after the [`Scanner`] chose how to normalize the value.
+	AttValueStart(&'a mut [u8; 1]),
 }
 impl<'a> From<Event<'a>> for Event_<'a> {
 	fn from(event: Event<'a>) -> Self {
@@ -233,7 +607,19 @@ impl<'a> From<Event<'a>> for Event_<'a> {
 #[derive(Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Event<'a> {
+	XmlDeclStart(&'a mut [u8; 5]),
+	XmlDeclEnd(&'a mut [u8; 2]),
+	TextDeclStart(&'a mut [u8; 5]),
+	TextDeclEnd(&'a mut [u8; 2]),
 	VersionChunk(&'a mut [u8]),
+	EncNameChunk(&'a mut [u8]),
+	/// The closing quote of an encoding declaration.
+	///
+	/// Nothing past this quote has been scanned when this event is yielded,
+	/// so this is where a driver can switch to the declared encoding:
+	/// Any data still [filled](`StrBuf::filled`) into the buffer can be replaced with its re-decoded equivalent.
+	EncNameEnd(&'a mut [u8; 1]),
+	SDDecl(SDDecl<'a>),
 	CommentStart(&'a mut [u8; 4]),
 	CommentEnd(&'a mut [u8; 3]),
 	CommentChunk(&'a mut str),
@@ -253,21 +639,77 @@ pub enum Event<'a> {
 	EntityRefEnd(&'a mut [u8; 1]),
 	CDStart(&'a mut [u8; 9]),
 	CDEnd(&'a mut [u8; 3]),
+	CDataChunk(&'a mut str),
+	ElementDeclStart(&'a mut [u8; 9]),
+	ElementDeclEnd(&'a mut [u8; 1]),
+	EMPTY(&'a mut [u8; 5]),
+	ANY(&'a mut [u8; 3]),
+	PCDATA(&'a mut [u8; 7]),
+	/// The opening parenthesis of a content model group, mixed content declaration included.
+	GroupStart(&'a mut [u8; 1]),
+	GroupEnd(&'a mut [u8; 1]),
+	ChoiceSeparator(&'a mut [u8; 1]),
+	SeqSeparator(&'a mut [u8; 1]),
+	/// Applies to the directly preceding [`Event::NameChunk`]s or group.
+	Repetition(Repetition<'a>),
 	AttlistDeclStart(&'a mut [u8; 9]),
 	AttlistDeclEnd(&'a mut [u8; 1]),
 	StringType(StringType<'a>),
 	TokenizedType(TokenizedType<'a>),
+	NOTATION(&'a mut [u8; 8]),
+	NmtokenChunk(&'a mut str),
+	DefaultDecl(DefaultDecl<'a>),
+	EntityDeclStart(&'a mut [u8; 8]),
+	/// The `%` that makes an entity declaration a parameter entity declaration.
+	PEDeclMarker(&'a mut [u8; 1]),
+	EntityDeclEnd(&'a mut [u8; 1]),
+	EntityValueStart(&'a mut [u8; 1]),
+	EntityValueChunk(&'a mut str),
+	EntityValueEnd(&'a mut [u8; 1]),
+	NDATA(&'a mut [u8; 5]),
+	ConditionalSectStart(&'a mut [u8; 3]),
+	INCLUDE(&'a mut [u8; 7]),
+	IGNORE(&'a mut [u8; 6]),
+	ConditionalSectEnd(&'a mut [u8; 3]),
+	/// Content of an ignored conditional section, including that of any sections nested in it.
+	///
+	/// Not reported if the [`Scanner`] is set to [drop ignored content](`Scanner::with_drop_ignored_content`).
+	IgnoreSectChunk(&'a mut str),
 	NotationDeclStart(&'a mut [u8; 10]),
 	SYSTEM(&'a mut [u8; 6]),
 	PUBLIC(&'a mut [u8; 6]),
+	SystemLiteralStart(&'a mut [u8; 1]),
+	SystemLiteralChunk(&'a mut str),
+	SystemLiteralEnd(&'a mut [u8; 1]),
+	PubidLiteralStart(&'a mut [u8; 1]),
+	PubidLiteralChunk(&'a mut str),
+	PubidLiteralEnd(&'a mut [u8; 1]),
 	NotationDeclEnd(&'a mut [u8; 1]),
 	NameChunk(&'a mut str),
+	AttributeNameChunk(&'a mut str),
+	AttValueStart(&'a mut [u8; 1]),
+	AttValueChunk(&'a mut str),
+	AttValueEnd(&'a mut [u8; 1]),
 	CharDataChunk(&'a mut str),
 	CharRefDecimalStart(&'a mut [u8; 2]),
 	CharRefHexadecimalStart(&'a mut [u8; 3]),
 	CharRefDecimalChunk(&'a mut [u8]),
 	CharRefHexadecimalChunk(&'a mut [u8]),
-	CharRefEnd(&'a mut [u8; 1]),
+	/// The `;` of a character reference, along with the referenced character.
+	CharRefEnd(&'a mut [u8; 1], char),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SDDecl<'a> {
+	Yes(&'a mut [u8; 3]),
+	No(&'a mut [u8; 2]),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Repetition<'a> {
+	ZeroOrOne(&'a mut [u8; 1]),
+	ZeroOrMore(&'a mut [u8; 1]),
+	OneOrMore(&'a mut [u8; 1]),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -288,6 +730,14 @@ pub enum TokenizedType<'a> {
 	NMTOKENS(&'a mut [u8; 8]),
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum DefaultDecl<'a> {
+	REQUIRED(&'a mut [u8; 9]),
+	IMPLIED(&'a mut [u8; 8]),
+	/// Followed by the default value's [`Event::AttValueStart`].
+	FIXED(&'a mut [u8; 6]),
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
 	ExpectedLiteral(&'static [u8]),
@@ -304,17 +754,57 @@ pub enum Error {
 	ExpectedEndTagEnd,
 	Expected25Eq,
 	Expected10AttValue,
+	Expected2Char,
+	/// An XML 1.1 [`RestrictedChar`](https://www.w3.org/TR/xml11/#NT-RestrictedChar) that wasn't written as a character reference.
+	RestrictedChar,
+	/// The document's XML version (`1.0` if it has no XML declaration) isn't accepted by the [`VersionPolicy`] in use.
+	VersionNotAllowed(VersionPolicy),
+	/// A character reference to a character that isn't a legal [`Char`](https://www.w3.org/TR/xml11/#NT-Char) in the document's XML version.
+	IllegalCharRef,
 	Expected17PITarget,
+	ReservedPITarget,
+	MisplacedXMLDecl,
 	ExpectedWhitespaceOrPIEnd,
 	Expected28bIntSubset,
+	Expected31ExtSubsetDecl,
+	Expected43Content,
+	UnexpectedEndTag,
+	/// A literal `]]>` in character data.
+	CDEndInCharData,
+	/// An `&` in content that starts neither a character nor an entity reference.
+	StrayAmpersand,
+	/// A `<` in content that starts neither a tag nor other markup,
+	/// for example because it's followed by a character that can't start a name.
+	StrayLessThan,
+	ExpectedElementDeclEnd,
+	Expected46ContentSpec,
+	Expected48Cp,
+	ExpectedGroupSeparatorOrEnd,
+	MixedChoiceAndSeq,
 	ExpectedAttlistDeclEnd,
+	Expected54AttType,
+	Expected60DefaultDecl,
+	Expected7Nmtoken,
+	ExpectedEntityDeclEnd,
+	Expected73EntityDef,
+	Expected74PEDef,
+	ExpectedINCLUDEorIGNORE,
+	ExpectedConditionalSectEnd,
 	ExpectedXMLDeclEnd,
+	ExpectedTextDeclEnd,
+	Expected80EncodingDecl,
+	ExpectedYesOrNo,
+	Expected81EncName,
 	ExpectedSYSTEMorPUBLIC,
 	Expected12PubidLiteral,
 	Expected11SystemLiteral,
+	FragmentIdentifierInSystemLiteral,
+	Expected13PubidChar,
 	ExpectedNotationDeclEnd,
 	UnsupportedXmlVersion,
 	ExpectedDecimalDigit,
 	ExpectedHexadecimalDigit,
 	DoubleDashInComment,
+	/// A comment ending in `--->`.
+	DashBeforeCommentEnd,
 }
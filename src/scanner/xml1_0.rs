@@ -1,11 +1,11 @@
 #![allow(clippy::enum_glob_use, non_snake_case, clippy::match_same_arms)]
 
 use super::{
-	Error, Event, Event_, MoreInputRequired, Next::*, NextFnR, RetVal, RetVal::*, StringType,
-	TokenizedType,
+	DefaultDecl, Error, Event, Event_, MoreInputRequired, Next::*, NextFnR, Repetition, RetVal,
+	RetVal::*, SDDecl, StringType, TokenizedType,
 };
-use crate::buffer::StrBuf;
-use std::any::type_name;
+use crate::buffer::{Indeterminate, LineEndings, StrBuf};
+use std::{any::type_name, str::from_utf8_mut};
 use tap::Pipe;
 use tracing::instrument;
 
@@ -39,6 +39,80 @@ macro_rules! CallState {
 pub(super) const START_AT_VERSION_NUMBER_SINGLE_QUOTE: u8 = u8::MAX;
 pub(super) const START_AT_VERSION_NUMBER_DOUBLE_QUOTE: u8 = u8::MAX - 1;
 
+/// Entry state of [`Grammar::Name`] for attribute names.
+const ATTRIBUTE_NAME: u8 = 2;
+
+/// Entry state of [`Grammar::children`] right after its opening parenthesis.
+const CHILDREN_AFTER_GROUP_START: u8 = 1;
+
+/// Added to [`Grammar::AttValue`]'s state after its opening quote to normalize whitespace in the value to spaces.
+pub(super) const ATT_VALUE_CDATA_NORMALIZATION: u8 = 20;
+
+/// Added to [`Grammar::AttValue`]'s state after its opening quote to also trim and collapse spaces in the value.
+pub(super) const ATT_VALUE_TOKENIZED_NORMALIZATION: u8 = 40;
+
+/// Whether `c` is whitespace once line endings are normalized.
+fn is_normalized_whitespace(line_endings: LineEndings, c: char) -> bool {
+	matches!(c, ' ' | '\t' | '\n' | '\r')
+		|| (line_endings == LineEndings::Xml1_1 && matches!(c, '\u{85}' | '\u{2028}'))
+}
+
+/// The length of the character at the start of `bytes` iff it is whitespace once line endings are normalized.
+fn normalized_whitespace_len(
+	line_endings: LineEndings,
+	bytes: &[u8],
+) -> Result<Option<usize>, Indeterminate> {
+	Ok(match (line_endings, bytes) {
+		(_, [b' ' | b'\t' | b'\n' | b'\r', ..]) => Some(1),
+		(LineEndings::Xml1_1, [0xC2, 0x85, ..]) => Some(2),
+		(LineEndings::Xml1_1, [0xE2, 0x80, 0xA8, ..]) => Some(3),
+		(_, []) => return Err(Indeterminate::new()),
+		(LineEndings::Xml1_1, [0xC2 | 0xE2] | [0xE2, 0x80]) => return Err(Indeterminate::new()),
+		_ => None,
+	})
+}
+
+/// [3], as [`Grammar::S`] with the whitespace of `line_endings`.
+fn shift_whitespace<'a>(
+	line_endings: LineEndings,
+	buffer: &mut StrBuf<'a>,
+	state: u8,
+) -> NextFnR<'a> {
+	match state {
+		0 => match normalized_whitespace_len(line_endings, buffer.filled())? {
+			Some(len) => {
+				buffer.shift_filled(len).expect("unreachable");
+				Continue(1)
+			}
+			None => Exit(Reject),
+		},
+		1 => {
+			while let Some(len) = normalized_whitespace_len(line_endings, buffer.filled())? {
+				buffer.shift_filled(len).expect("unreachable");
+			}
+			Exit(Accept)
+		}
+		_ => unreachable!(),
+	}
+	.pipe(Ok)
+}
+
+/// Views a known ASCII sequence as text.
+fn ascii_str<const LEN: usize>(bytes: &mut [u8; LEN]) -> &mut str {
+	from_utf8_mut(bytes).expect("unreachable")
+}
+
+/// Shifts the optional `?`, `*` or `+` suffix of [`Grammar::children`] and [`Grammar::cp`].
+fn shift_repetition<'a>(buffer: &mut StrBuf<'a>) -> Result<Option<Repetition<'a>>, Indeterminate> {
+	Ok(if let Some(zero_or_one) = buffer.shift_known_array(b"?")? {
+		Some(Repetition::ZeroOrOne(zero_or_one))
+	} else if let Some(zero_or_more) = buffer.shift_known_array(b"*")? {
+		Some(Repetition::ZeroOrMore(zero_or_more))
+	} else {
+		buffer.shift_known_array(b"+")?.map(Repetition::OneOrMore)
+	})
+}
+
 pub(super) enum Xml1_0 {}
 
 /// Baseline grammar, but with downgrade entry points here.
@@ -104,13 +178,13 @@ impl Grammar for Xml1_0 {
 	#[instrument(ret(Debug))]
 	fn VersionInfo<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => Call!(1, S),
+			(0, _) => Call!(1, S_in_XMLDecl),
 			(1, Accept) => match buffer.shift_known_array(b"version")? {
 				Some(_) => Continue(2),
 				None => Error(Error::ExpectedLiteral(b"version")),
 			},
 			(1, Reject) => Exit(Reject),
-			(2, _) => Call!(3, Eq),
+			(2, _) => Call!(3, Eq_in_XMLDecl),
 			(3, Accept) => match buffer.shift_known_array(b"'")? {
 				Some(_) => Continue(4),
 				None => match buffer.shift_known_array(b"\"")? {
@@ -136,26 +210,39 @@ impl Grammar for Xml1_0 {
 	}
 
 	/// [23]
-	/// Start tokens: `<?xml`
+	/// Start tokens: `<?xml` followed by [`S`]
 	#[instrument(ret(Debug))]
 	fn XMLDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => match buffer.shift_known_array(b"<?xml")? {
-				Some(_) => Continue(1),
-				None => Exit(Reject),
+			(0, _) => match buffer.filled() {
+				// Anything else is a processing instruction (or not well-formed).
+				[b'<', b'?', b'x', b'm', b'l', 0x20 | 0x9 | 0xD | 0xA, ..] => Yield(
+					1,
+					Event::XmlDeclStart(
+						buffer
+							.shift_known_array(b"<?xml")
+							.expect("unreachable")
+							.expect("unreachable"),
+					)
+					.into(),
+				),
+				filled if b"<?xml".starts_with(filled) => return Err(MoreInputRequired::new()),
+				_ => Exit(Reject),
 			},
 			(1, _) => Call!(2, VersionInfo),
-			(2, Accept) => Call!(21, S),
+			(2, Accept) => Call!(21, S_in_XMLDecl),
 			(2, Reject) => Error(Error::Expected24VersionInfo),
 			(21, Accept) => Call!(3, EncodingDecl_minus_initial_S),
 			(21, Reject) => Continue(5),
-			(3, Accept) => Call!(31, S),
+			(3, Accept) => Call!(31, S_in_XMLDecl),
 			(31, Accept) | (3, Reject) => Call!(4, SDDecl_minus_initial_S),
-			(4, _) => Call!(5, S),
+			(31, Reject) => Continue(5),
+			(4, _) => Call!(5, S_in_XMLDecl),
 			(5, _) => match buffer.shift_known_array(b"?>")? {
-				Some(_) => Exit(Accept),
+				Some(end) => Yield(6, Event::XmlDeclEnd(end).into()),
 				None => Error(Error::ExpectedXMLDeclEnd),
 			},
+			(6, _) => Exit(Accept),
 
 			(
 				start_at @ (START_AT_VERSION_NUMBER_SINGLE_QUOTE
@@ -169,7 +256,26 @@ impl Grammar for Xml1_0 {
 	}
 }
 
+/// [`Xml1_0`], but for the external subset, where parameter entity references may appear within markup declarations.
+pub(super) enum Xml1_0ExtSubset {}
+
+impl Grammar for Xml1_0ExtSubset {
+	const PE_REFERENCES_IN_MARKUPDECL: bool = true;
+
+	fn test_Char(c: char) -> bool {
+		Xml1_0::test_Char(c)
+	}
+}
+
 pub(super) trait Grammar {
+	/// Line breaks normalized in text, as per 2.11.
+	const LINE_ENDINGS: LineEndings = LineEndings::Xml1_0;
+
+	/// Whether a [`PEReference`] may stand in for a token within markup declarations, as per 2.8.
+	///
+	/// > That's only the case in the external subset, and the references are reported rather than expanded there.
+	const PE_REFERENCES_IN_MARKUPDECL: bool = false;
+
 	/// [1]
 	#[instrument(ret(Debug))]
 	fn document<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
@@ -188,30 +294,65 @@ pub(super) trait Grammar {
 	/// [2]
 	fn test_Char(c: char) -> bool;
 
+	/// [2a]
+	///
+	/// > Only XML 1.1 has restricted characters.
+	fn test_RestrictedChar(_c: char) -> bool {
+		false
+	}
+
+	/// [2] [2a]
+	///
+	/// > Restricted characters must only appear as character references, so this is what text is made of.
+	fn test_Char_minus_RestrictedChar(c: char) -> bool {
+		Self::test_Char(c) && !Self::test_RestrictedChar(c)
+	}
+
+	/// The error for a character at the start of `buffer` that failed [`test_Char_minus_RestrictedChar`].
+	///
+	/// # Errors
+	///
+	/// Iff the character isn't complete yet.
+	fn Char_error(buffer: &StrBuf) -> Result<Error, Indeterminate> {
+		Ok(match buffer.peek_char(0)? {
+			Ok(c) if Self::test_RestrictedChar(c) => Error::RestrictedChar,
+			Ok(_) => Error::Expected2Char,
+			Err(error) => Error::Utf8Error(error),
+		})
+	}
+
 	/// [3]
 	/// Start tokens: *0x20* | *0x9* | *0xD* | *0xA*
+	///
+	/// > Line endings are normalized before parsing, so this also accepts the additional ones in [`LINE_ENDINGS`](`Grammar::LINE_ENDINGS`).
 	#[instrument(ret(Debug))]
 	fn S<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		match (state, ret_val) {
-			(0, _) => match buffer.shift_known_array(&[0x20])?.is_some()
-				|| buffer.shift_known_array(&[0x9])?.is_some()
-				|| buffer.shift_known_array(&[0xD])?.is_some()
-				|| buffer.shift_known_array(&[0xA])?.is_some()
-			{
-				true => Continue(1),
-				false => Exit(Reject),
-			},
-			(1, _) => {
-				while buffer.shift_known_array(&[0x20])?.is_some()
-					|| buffer.shift_known_array(&[0x9])?.is_some()
-					|| buffer.shift_known_array(&[0xD])?.is_some()
-					|| buffer.shift_known_array(&[0xA])?.is_some()
-				{}
-				Exit(Accept)
-			}
-			_ => unreachable!(),
+		shift_whitespace(Self::LINE_ENDINGS, buffer, state)
+	}
+
+	/// [3]
+	/// Start tokens: *0x20* | *0x9* | *0xD* | *0xA*
+	///
+	/// > XML 1.1's additional line breaks aren't whitespace within XML and text declarations, as per 2.11.
+	#[instrument(ret(Debug))]
+	fn S_in_XMLDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		shift_whitespace(LineEndings::Xml1_0, buffer, state)
+	}
+
+	/// Whether `buffer` starts with a [`PEReference`] that stands in for a token within a markup declaration.
+	///
+	/// # Errors
+	///
+	/// Iff that can't be determined yet.
+	fn test_PEReference_in_markupdecl(buffer: &StrBuf) -> Result<bool, Indeterminate> {
+		if !Self::PE_REFERENCES_IN_MARKUPDECL {
+			return Ok(false);
 		}
-		.pipe(Ok)
+		Ok(match buffer.filled() {
+			[] => return Err(Indeterminate::new()),
+			[b'%', ..] => matches!(buffer.peek_char(1)?, Ok(c) if Self::test_NameStartChar(c)),
+			_ => false,
+		})
 	}
 
 	/// [4]
@@ -250,21 +391,44 @@ pub(super) trait Grammar {
 	}
 
 	/// [5]
+	///
+	/// Entered at [`ATTRIBUTE_NAME`], this yields [`Event::AttributeNameChunk`]s instead of [`Event::NameChunk`]s.
 	#[instrument(ret(Debug))]
 	fn Name<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
 			(0, _) => {
-				match buffer
-					.shift_chars_start_while(Self::test_NameStartChar, Self::test_NameChar)?
-				{
-					Ok(x) if x.is_empty() => Exit(Reject),
-					Ok(chunk) => Yield(1, Event::NameChunk(chunk).into()),
+				match buffer.shift_chars_start_while(
+					Self::test_NameStartChar,
+					Self::test_NameChar,
+					Self::LINE_ENDINGS,
+				)? {
+					Ok(chunk) if !chunk.is_empty() => Yield(1, Event::NameChunk(chunk).into()),
+					Ok(_) => Exit(Reject),
+					Err(error) => Error(Error::Utf8Error(error)),
+				}
+			}
+			(1, _) => match buffer.shift_chars_while(Self::test_NameChar, Self::LINE_ENDINGS)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::NameChunk(chunk).into()),
+				Ok(_) => Exit(Accept),
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+
+			(ATTRIBUTE_NAME, _) => {
+				match buffer.shift_chars_start_while(
+					Self::test_NameStartChar,
+					Self::test_NameChar,
+					Self::LINE_ENDINGS,
+				)? {
+					Ok(chunk) if !chunk.is_empty() => {
+						Yield(3, Event::AttributeNameChunk(chunk).into())
+					}
+					Ok(_) => Exit(Reject),
 					Err(error) => Error(Error::Utf8Error(error)),
 				}
 			}
-			(1, _) => match buffer.shift_chars_while(Self::test_NameChar)? {
-				Ok(x) if x.is_empty() => Exit(Accept),
-				Ok(chunk) => Yield(1, Event::NameChunk(chunk).into()),
+			(3, _) => match buffer.shift_chars_while(Self::test_NameChar, Self::LINE_ENDINGS)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(3, Event::AttributeNameChunk(chunk).into()),
+				Ok(_) => Exit(Accept),
 				Err(error) => Error(Error::Utf8Error(error)),
 			},
 
@@ -273,22 +437,300 @@ pub(super) trait Grammar {
 		.pipe(Ok)
 	}
 
+	/// [7]
+	#[instrument(ret(Debug))]
+	fn Nmtoken<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_chars_while(Self::test_NameChar, Self::LINE_ENDINGS)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::NmtokenChunk(chunk).into()),
+				Ok(_) => Exit(Reject),
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(1, _) => match buffer.shift_chars_while(Self::test_NameChar, Self::LINE_ENDINGS)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::NmtokenChunk(chunk).into()),
+				Ok(_) => Exit(Accept),
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
 	/// [10]
+	/// Start tokens: `'` | `"`
+	///
+	/// > The [`Scanner`](`super::Scanner`) may shift the state after the opening quote
+	/// > by [`ATT_VALUE_CDATA_NORMALIZATION`] or [`ATT_VALUE_TOKENIZED_NORMALIZATION`].
 	#[instrument(ret(Debug))]
 	fn AttValue<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		let quote = if state % 20 < 10 { b"'" } else { b"\"" };
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(quote) = buffer.shift_known_array(b"'")? {
+					Yield(1, Event_::AttValueStart(quote))
+				} else if let Some(quote) = buffer.shift_known_array(b"\"")? {
+					Yield(11, Event_::AttValueStart(quote))
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1 | 11 | 21 | 31, _) => match buffer.shift_chars_while_delimited(
+				|c| c != '<' && c != '&' && Self::test_Char_minus_RestrictedChar(c),
+				quote,
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => {
+					if state > 20 {
+						// Line endings are normalized already, except in replacement text.
+						for byte in unsafe {
+							//SAFETY: Only ASCII is replaced with ASCII.
+							chunk.as_bytes_mut()
+						} {
+							if matches!(byte, b'\t' | b'\n' | b'\r') {
+								*byte = b' ';
+							}
+						}
+					}
+					Yield(state, Event::AttValueChunk(chunk).into())
+				}
+				Ok(_) => match buffer.shift_known_array(quote)? {
+					Some(end) => Yield(3, Event::AttValueEnd(end).into()),
+					None => Continue(state + 1),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(2 | 12 | 22 | 32 | 43 | 53, _) => match buffer.filled() {
+				[b'&', ..] => Call!(state - 1, Reference),
+				[b'<', ..] => Error(Error::UnexpectedSequence(b"<")),
+				_ => Error(Self::Char_error(buffer)?),
+			},
+			(3, _) => Exit(Accept),
+
+			// Leading whitespace.
+			(41 | 51, _) => match buffer.shift_chars_while(
+				|c| is_normalized_whitespace(Self::LINE_ENDINGS, c),
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(x) if !x.is_empty() => Continue(state),
+				Ok(_) => Continue(state + 1),
+				// Reported as part of the value.
+				Err(_) => Continue(state + 1),
+			},
+			(42 | 52, _) => match buffer.shift_chars_while_delimited(
+				|c| {
+					c != '<'
+						&& c != '&' && !is_normalized_whitespace(Self::LINE_ENDINGS, c)
+						&& Self::test_Char_minus_RestrictedChar(c)
+				},
+				quote,
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(state, Event::AttValueChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(quote)? {
+					Some(end) => Yield(3, Event::AttValueEnd(end).into()),
+					None => match normalized_whitespace_len(Self::LINE_ENDINGS, buffer.filled())? {
+						Some(_) => Continue(state + 2),
+						None => Continue(state + 1),
+					},
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			// Whitespace after content, collapsed into a single space unless trailing.
+			(44 | 54, _) => {
+				let filled = buffer.filled();
+				let (mut len, mut last) = (0, 0);
+				let followed_by_quote = loop {
+					match normalized_whitespace_len(Self::LINE_ENDINGS, &filled[len..]) {
+						Ok(Some(whitespace_len)) => {
+							last = len;
+							len += whitespace_len;
+						}
+						Ok(None) => break filled[len..].starts_with(quote),
+						// Keeps one whitespace character around so the buffer doesn't clog.
+						Err(_) if last > 0 => {
+							buffer.shift_filled(last).expect("unreachable");
+							return Ok(Continue(state));
+						}
+						Err(more_input_required) => return Err(more_input_required.into()),
+					}
+				};
+
+				let whitespace = buffer.shift_filled(len).expect("unreachable");
+				if followed_by_quote {
+					Continue(state - 2)
+				} else {
+					whitespace[0] = b' ';
+					Yield(
+						state - 2,
+						Event::AttValueChunk(
+							from_utf8_mut(&mut whitespace[..1]).expect("unreachable"),
+						)
+						.into(),
+					)
+				}
+			}
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [9]
+	/// Start tokens: `'` | `"`
+	#[instrument(ret(Debug))]
+	fn EntityValue<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(quote) = buffer.shift_known_array(b"'")? {
+					Yield(1, Event::EntityValueStart(quote).into())
+				} else if let Some(quote) = buffer.shift_known_array(b"\"")? {
+					Yield(11, Event::EntityValueStart(quote).into())
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1, _) => match buffer.shift_chars_while_delimited(
+				|c| c != '%' && c != '&' && Self::test_Char_minus_RestrictedChar(c),
+				b"'",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::EntityValueChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(b"'")? {
+					Some(end) => Yield(3, Event::EntityValueEnd(end).into()),
+					None => Continue(2),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(11, _) => match buffer.shift_chars_while_delimited(
+				|c| c != '%' && c != '&' && Self::test_Char_minus_RestrictedChar(c),
+				b"\"",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(11, Event::EntityValueChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(b"\"")? {
+					Some(end) => Yield(3, Event::EntityValueEnd(end).into()),
+					None => Continue(12),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(2 | 12, _) => match buffer.filled() {
+				[b'%', ..] => Call!(state - 1, PEReference),
+				[b'&', ..] => Call!(state - 1, Reference),
+				_ => Error(Self::Char_error(buffer)?),
+			},
+			(3, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [11]
+	/// Start tokens: `'` | `"`
+	///
+	/// > Fragment identifiers are an error here, so `#` isn't part of any chunk.
 	#[instrument(ret(Debug))]
 	fn SystemLiteral<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(quote) = buffer.shift_known_array(b"'")? {
+					Yield(1, Event::SystemLiteralStart(quote).into())
+				} else if let Some(quote) = buffer.shift_known_array(b"\"")? {
+					Yield(11, Event::SystemLiteralStart(quote).into())
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1, _) => match buffer.shift_chars_while_delimited(
+				|c| c != '#' && Self::test_Char_minus_RestrictedChar(c),
+				b"'",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::SystemLiteralChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(b"'")? {
+					Some(end) => Yield(3, Event::SystemLiteralEnd(end).into()),
+					None => Continue(2),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(11, _) => match buffer.shift_chars_while_delimited(
+				|c| c != '#' && Self::test_Char_minus_RestrictedChar(c),
+				b"\"",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => {
+					Yield(11, Event::SystemLiteralChunk(chunk).into())
+				}
+				Ok(_) => match buffer.shift_known_array(b"\"")? {
+					Some(end) => Yield(3, Event::SystemLiteralEnd(end).into()),
+					None => Continue(12),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(2 | 12, _) => match buffer.filled() {
+				[b'#', ..] => Error(Error::FragmentIdentifierInSystemLiteral),
+				_ => Error(Self::Char_error(buffer)?),
+			},
+			(3, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [12]
+	/// Start tokens: `'` | `"`
 	#[instrument(ret(Debug))]
 	fn PubidLiteral<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(quote) = buffer.shift_known_array(b"'")? {
+					Yield(1, Event::PubidLiteralStart(quote).into())
+				} else if let Some(quote) = buffer.shift_known_array(b"\"")? {
+					Yield(11, Event::PubidLiteralStart(quote).into())
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1, _) => match buffer.shift_chars_while_delimited(
+				Self::test_PubidChar,
+				b"'",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(1, Event::PubidLiteralChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(b"'")? {
+					Some(end) => Yield(3, Event::PubidLiteralEnd(end).into()),
+					None => Error(Error::Expected13PubidChar),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(11, _) => match buffer.shift_chars_while_delimited(
+				Self::test_PubidChar,
+				b"\"",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(11, Event::PubidLiteralChunk(chunk).into()),
+				Ok(_) => match buffer.shift_known_array(b"\"")? {
+					Some(end) => Yield(3, Event::PubidLiteralEnd(end).into()),
+					None => Error(Error::Expected13PubidChar),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			(3, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [13]
+	fn test_PubidChar(c: char) -> bool {
+		matches!(c,
+			| '\u{20}'
+			| '\u{D}'
+			| '\u{A}'
+			| 'a'..='z'
+			| 'A'..='Z'
+			| '0'..='9'
+			| '-' | '\'' | '(' | ')' | '+' | ',' | '.' | '/' | ':'
+			| '=' | '?' | ';' | '!' | '*' | '#' | '@' | '$' | '_' | '%'
+		)
 	}
 
 	/// [14]
@@ -297,11 +739,15 @@ pub(super) trait Grammar {
 		match (state, ret_val) {
 			(0, _) => {
 				match buffer.shift_chars_while_delimited(
-					|c| c != '<' && c != '&' && Self::test_Char(c),
+					|c| c != '<' && c != '&' && Self::test_Char_minus_RestrictedChar(c),
 					b"]]>",
+					Self::LINE_ENDINGS,
 				)? {
-					Ok(x) if x.is_empty() => Exit(Accept),
-					Ok(chunk) => Yield(0, Event::CharDataChunk(chunk).into()),
+					Ok(chunk) if !chunk.is_empty() => Yield(0, Event::CharDataChunk(chunk).into()),
+					Ok(_) => match buffer.filled() {
+						[b']', b']', b'>', ..] => Error(Error::CDEndInCharData),
+						_ => Exit(Accept),
+					},
 					Err(error) => Error(Error::Utf8Error(error)),
 				}
 			}
@@ -329,20 +775,17 @@ pub(super) trait Grammar {
 				None => Error(Error::ExpectedWhitespaceOrPIEnd),
 			},
 			(4, _) => {
-				match buffer.shift_chars_delimited(b"?>")? {
+				match buffer.shift_chars_while_delimited(
+					Self::test_Char_minus_RestrictedChar,
+					b"?>",
+					Self::LINE_ENDINGS,
+				)? {
 					Err(error) => Error(Error::Utf8Error(error)),
-					Ok(valid) if valid.is_empty() => Yield(
-						5,
-						Event::PIEnd(
-							buffer
-								.shift_known_array(b"?>")
-								.expect("unreachable")
-								.expect("unreachable"),
-						)
-						.into(),
-					),
-					//BUG: Detect disallowed characters!
-					Ok(valid) => Yield(4, Event::PIChunk(valid).into()),
+					Ok(valid) if !valid.is_empty() => Yield(4, Event::PIChunk(valid).into()),
+					Ok(_) => match buffer.shift_known_array(b"?>")? {
+						Some(end) => Yield(5, Event::PIEnd(end).into()),
+						None => Error(Self::Char_error(buffer)?),
+					},
 				}
 			}
 			(5, _) => Exit(Accept),
@@ -352,9 +795,30 @@ pub(super) trait Grammar {
 	}
 
 	/// [17]
+	///
+	/// > Targets matching `[Xx][Mm][Ll]` are rejected with an error rather than [`Reject`],
+	/// > since every other production that could start here does so with a different token.
 	#[instrument(ret(Debug))]
 	fn PITarget<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.filled() {
+				[b'X' | b'x', b'M' | b'm', b'L' | b'l', ..]
+					if matches!(buffer.peek_char(3)?, Ok(c) if Self::test_NameChar(c)) =>
+				{
+					// Longer names like `xml-stylesheet` are fine.
+					Call!(1, Name)
+				}
+				[b'x', b'm', b'l', ..] => Error(Error::MisplacedXMLDecl),
+				[b'X' | b'x', b'M' | b'm', b'L' | b'l', ..] => Error(Error::ReservedPITarget),
+				[] | [b'X' | b'x'] | [b'X' | b'x', b'M' | b'm'] => {
+					return Err(MoreInputRequired::new())
+				}
+				_ => Call!(1, Name),
+			},
+			(1, ret_val) => Exit(ret_val),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [18]
@@ -391,9 +855,26 @@ pub(super) trait Grammar {
 	}
 
 	/// [20]
+	///
+	/// Never rejects, as the section's content may be empty.
 	#[instrument(ret(Debug))]
 	fn CData<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_chars_while_delimited(
+				Self::test_Char_minus_RestrictedChar,
+				b"]]>",
+				Self::LINE_ENDINGS,
+			)? {
+				Ok(chunk) if !chunk.is_empty() => Yield(0, Event::CDataChunk(chunk).into()),
+				Ok(_) => match buffer.filled() {
+					[b']', b']', b'>', ..] => Exit(Accept),
+					_ => Error(Self::Char_error(buffer)?),
+				},
+				Err(error) => Error(Error::Utf8Error(error)),
+			},
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [21]
@@ -438,14 +919,29 @@ pub(super) trait Grammar {
 				None => Exit(Reject),
 			},
 			(1, _) => {
-				match buffer.shift_chars_delimited(b"--")? {
+				match buffer.shift_chars_while_delimited(
+					Self::test_Char_minus_RestrictedChar,
+					b"--",
+					Self::LINE_ENDINGS,
+				)? {
 					Err(error) => Error(Error::Utf8Error(error)),
-					Ok(valid) if valid.is_empty() => match buffer.shift_known_array(b"-->")? {
-						Some(end) => Yield(2, Event::CommentEnd(end).into()),
-						None => Error(Error::DoubleDashInComment),
+					Ok(valid) if !valid.is_empty() => Yield(1, Event::CommentChunk(valid).into()),
+					Ok(_) => match buffer.filled() {
+						[b'-', b'-', b'>', ..] => Yield(
+							2,
+							Event::CommentEnd(
+								buffer
+									.shift_known_array(b"-->")
+									.expect("unreachable")
+									.expect("unreachable"),
+							)
+							.into(),
+						),
+						[b'-', b'-', b'-', b'>', ..] => Error(Error::DashBeforeCommentEnd),
+						b"--" | b"---" => return Err(MoreInputRequired::new()),
+						[b'-', b'-', ..] => Error(Error::DoubleDashInComment),
+						_ => Error(Self::Char_error(buffer)?),
 					},
-					//BUG: Detect disallowed characters!
-					Ok(valid) => Yield(1, Event::CommentChunk(valid).into()),
 				}
 			}
 			(2, _) => Exit(Accept),
@@ -455,26 +951,39 @@ pub(super) trait Grammar {
 	}
 
 	/// [23]
-	/// Start tokens: `<?xml`
+	/// Start tokens: `<?xml` followed by [`S`]
 	#[instrument(ret(Debug))]
 	fn XMLDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => match buffer.shift_known_array(b"<?xml")? {
-				Some(_) => Continue(1),
-				None => Exit(Reject),
+			(0, _) => match buffer.filled() {
+				// Anything else is a processing instruction (or not well-formed).
+				[b'<', b'?', b'x', b'm', b'l', 0x20 | 0x9 | 0xD | 0xA, ..] => Yield(
+					1,
+					Event::XmlDeclStart(
+						buffer
+							.shift_known_array(b"<?xml")
+							.expect("unreachable")
+							.expect("unreachable"),
+					)
+					.into(),
+				),
+				filled if b"<?xml".starts_with(filled) => return Err(MoreInputRequired::new()),
+				_ => Exit(Reject),
 			},
 			(1, _) => Call!(2, VersionInfo),
-			(2, Accept) => Call!(21, S),
+			(2, Accept) => Call!(21, S_in_XMLDecl),
 			(2, Reject) => Error(Error::Expected24VersionInfo),
 			(21, Accept) => Call!(3, EncodingDecl_minus_initial_S),
 			(21, Reject) => Continue(5),
-			(3, Accept) => Call!(31, S),
+			(3, Accept) => Call!(31, S_in_XMLDecl),
 			(31, Accept) | (3, Reject) => Call!(4, SDDecl_minus_initial_S),
-			(4, _) => Call!(5, S),
+			(31, Reject) => Continue(5),
+			(4, _) => Call!(5, S_in_XMLDecl),
 			(5, _) => match buffer.shift_known_array(b"?>")? {
-				Some(_) => Exit(Accept),
+				Some(end) => Yield(6, Event::XmlDeclEnd(end).into()),
 				None => Error(Error::ExpectedXMLDeclEnd),
 			},
+			(6, _) => Exit(Accept),
 
 			_ => unreachable!(),
 		}
@@ -485,13 +994,13 @@ pub(super) trait Grammar {
 	#[instrument(ret(Debug))]
 	fn VersionInfo<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => Call!(1, S),
+			(0, _) => Call!(1, S_in_XMLDecl),
 			(1, Accept) => match buffer.shift_known_array(b"version")? {
 				Some(_) => Continue(2),
 				None => Error(Error::ExpectedLiteral(b"version")),
 			},
 			(1, Reject) => Exit(Reject),
-			(2, _) => Call!(3, Eq),
+			(2, _) => Call!(3, Eq_in_XMLDecl),
 			(3, Accept) => match buffer.shift_known_array(b"'")? {
 				Some(_) => Continue(4),
 				None => match buffer.shift_known_array(b"\"")? {
@@ -535,6 +1044,25 @@ pub(super) trait Grammar {
 		.pipe(Ok)
 	}
 
+	/// [25]
+	/// Start tokens: any
+	///
+	/// Like [`Eq`], but with [`S_in_XMLDecl`]. Never returns `Ok(Exit(Failure))`.
+	#[instrument(ret(Debug))]
+	fn Eq_in_XMLDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => Call!(1, S_in_XMLDecl),
+			(1, _) => match buffer.shift_known_array(b"=")? {
+				Some(_) => Continue(2),
+				None => Error(Error::ExpectedLiteral(b"=")),
+			},
+			(2, _) => Call!(3, S_in_XMLDecl),
+			(3, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
 	/// [26]
 	///
 	/// > \[sic\]. I.e.: Compliant XML 1.0 processors accept documents with other "1." version numbers,
@@ -548,12 +1076,18 @@ pub(super) trait Grammar {
 			},
 			(1, _) => match buffer.shift_bytes_while(|b| (b'0'..=b'9').contains(&b))? {
 				[] => Error(Error::ExpectedDecimalDigit.into()),
+				chunk if chunk == b"0" => Yield(3, Event::VersionChunk(chunk).into()),
 				chunk => Yield(2, Event::VersionChunk(chunk).into()),
 			},
 			(2, _) => match buffer.shift_bytes_while(|b| (b'0'..=b'9').contains(&b))? {
-				[] => Exit(Accept),
+				[] => Yield(4, Event_::ConfirmOtherVersion),
+				chunk => Yield(2, Event::VersionChunk(chunk).into()),
+			},
+			(3, _) => match buffer.shift_bytes_while(|b| (b'0'..=b'9').contains(&b))? {
+				[] => Yield(4, Event_::ConfirmVersion1_0),
 				chunk => Yield(2, Event::VersionChunk(chunk).into()),
 			},
+			(4, _) => Exit(Accept),
 			_ => unreachable!(),
 		}
 		.pipe(Ok)
@@ -657,7 +1191,7 @@ pub(super) trait Grammar {
 		match (state, ret_val) {
 			(0, _) => Call!(1, TextDecl),
 			(1, _) => Call!(2, extSubsetDecl),
-			(2, ret_val) => Exit(ret_val),
+			(2, _) => Error(Error::Expected31ExtSubsetDecl),
 			_ => unreachable!(),
 		}
 		.pipe(Ok)
@@ -670,20 +1204,53 @@ pub(super) trait Grammar {
 			(0, _) | (1 | 2 | 3, Accept) => Call!(1, markupdecl),
 			(1, Reject) => Call!(2, conditionalSect),
 			(2, Reject) => Call!(3, DeclSep),
-			(3, Reject) => Exit(Reject),
+			(3, Reject) => Exit(Accept),
 			_ => unreachable!(),
 		}
 		.pipe(Ok)
 	}
 
 	/// [32]
+	/// Start tokens: `standalone`
 	#[instrument(ret(Debug))]
 	fn SDDecl_minus_initial_S<'a>(
 		buffer: &mut StrBuf<'a>,
 		state: u8,
 		ret_val: RetVal,
 	) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"standalone")? {
+				Some(_) => Call!(1, Eq_in_XMLDecl),
+				None => Exit(Reject),
+			},
+			(1, Accept) => match buffer.shift_known_array(b"'")? {
+				Some(_) => Continue(2),
+				None => match buffer.shift_known_array(b"\"")? {
+					Some(_) => Continue(4),
+					None => Error(Error::ExpectedQuote),
+				},
+			},
+			(1, Reject) => unreachable!("`Eq` shouldn't fail."),
+			(2 | 4, _) => {
+				if let Some(yes) = buffer.shift_known_array(b"yes")? {
+					Yield(state + 1, Event::SDDecl(SDDecl::Yes(yes)).into())
+				} else if let Some(no) = buffer.shift_known_array(b"no")? {
+					Yield(state + 1, Event::SDDecl(SDDecl::No(no)).into())
+				} else {
+					Error(Error::ExpectedYesOrNo)
+				}
+			}
+			(3, _) => match buffer.shift_known_array(b"'")? {
+				Some(_) => Exit(Accept),
+				None => Error(Error::ExpectedLiteral(b"'")),
+			},
+			(5, _) => match buffer.shift_known_array(b"\"")? {
+				Some(_) => Exit(Accept),
+				None => Error(Error::ExpectedLiteral(b"\"")),
+			},
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [39], [40], [44]
@@ -724,7 +1291,7 @@ pub(super) trait Grammar {
 	#[instrument(ret(Debug))]
 	fn Attribute<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => Call!(1, Name),
+			(0, _) => CallState!(1, Name, ATTRIBUTE_NAME),
 			(1, Accept) => Call!(2, Eq),
 			(1, Reject) => Exit(Reject),
 			(2, Accept) => Call!(3, AttValue),
@@ -771,9 +1338,19 @@ pub(super) trait Grammar {
 			(2, _) => Call!(3, Comment),
 			(3, Reject) => Call!(4, CDSect),
 			(4, Reject) => Call!(5, PI),
-			(5, Reject) => Call!(6, element),
-			(6, Reject) => Call!(7, Reference),
-			(7, Reject) => Exit(Accept),
+			(5, Reject) => match buffer.filled() {
+				[b'<', ..] => match buffer.peek_char(1)? {
+					Ok(c) if Self::test_NameStartChar(c) => Call!(6, element),
+					_ => Error(Error::StrayLessThan),
+				},
+				[b'&', b'#', ..] => Call!(7, Reference),
+				[b'&', ..] => match buffer.peek_char(1)? {
+					Ok(c) if Self::test_NameStartChar(c) => Call!(7, Reference),
+					_ => Error(Error::StrayAmpersand),
+				},
+				_ => Error(Self::Char_error(buffer)?),
+			},
+			(6 | 7, Reject) => unreachable!("checked above"),
 			(2..=7, Accept) => Call!(1, CharData),
 			_ => unreachable!(),
 		}
@@ -781,9 +1358,195 @@ pub(super) trait Grammar {
 	}
 
 	/// [45]
+	/// Start tokens: `<!ELEMENT`
 	#[instrument(ret(Debug))]
 	fn elementdecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"<!ELEMENT")? {
+				Some(start) => Yield(1, Event::ElementDeclStart(start).into()),
+				None => Exit(Reject),
+			},
+			(1, _) => Call!(2, S),
+			(2, Accept) => Call!(3, Name),
+			(2, Reject) => Error(Error::Expected3Whitespace),
+			(3, Accept) => Call!(4, S),
+			(3, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(3, PEReference),
+			(3, Reject) => Error(Error::Expected5Name),
+			(4, Accept) => Call!(5, contentspec),
+			(4, Reject) => Error(Error::Expected3Whitespace),
+			(5, Accept) => Call!(6, S),
+			(5, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(5, PEReference),
+			(5, Reject) => Error(Error::Expected46ContentSpec),
+			(6, _) => match buffer.shift_known_array(b">")? {
+				Some(end) => Yield(7, Event::ElementDeclEnd(end).into()),
+				None => Error(Error::ExpectedElementDeclEnd),
+			},
+			(7, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [46] [51]
+	/// Start tokens: `EMPTY`, `ANY`, `(`
+	///
+	/// > [`Mixed`](https://www.w3.org/TR/xml/#NT-Mixed) and [`children`] can only be told apart after the opening parenthesis,
+	/// > so the former is flattened into this production.
+	#[instrument(ret(Debug))]
+	fn contentspec<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(empty) = buffer.shift_known_array(b"EMPTY")? {
+					Yield(12, Event::EMPTY(empty).into())
+				} else if let Some(any) = buffer.shift_known_array(b"ANY")? {
+					Yield(12, Event::ANY(any).into())
+				} else if let Some(start) = buffer.shift_known_array(b"(")? {
+					Yield(1, Event::GroupStart(start).into())
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1, _) => Call!(2, S),
+			(2, _) => match buffer.shift_known_array(b"#PCDATA")? {
+				Some(pcdata) => Yield(3, Event::PCDATA(pcdata).into()),
+				None => CallState!(11, children, CHILDREN_AFTER_GROUP_START),
+			},
+			(3, _) => Call!(4, S),
+			// Without names, the group may end in `)` or `)*`.
+			(4, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(5, Event::ChoiceSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(10, Event::GroupEnd(end).into())
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			(5, _) => Call!(6, S),
+			(6, _) => Call!(7, Name),
+			(7, Accept) => Call!(8, S),
+			(7, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(7, PEReference),
+			(7, Reject) => Error(Error::Expected5Name),
+			// With names, the group must end in `)*`.
+			(8, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(5, Event::ChoiceSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(9, Event::GroupEnd(end).into())
+				} else if buffer.shift_known_array(b",")?.is_some() {
+					Error(Error::MixedChoiceAndSeq)
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			(9, _) => match buffer.shift_known_array(b"*")? {
+				Some(zero_or_more) => Yield(
+					12,
+					Event::Repetition(Repetition::ZeroOrMore(zero_or_more)).into(),
+				),
+				None => Error(Error::ExpectedLiteral(b"*")),
+			},
+			(10, _) => match buffer.shift_known_array(b"*")? {
+				Some(zero_or_more) => Yield(
+					12,
+					Event::Repetition(Repetition::ZeroOrMore(zero_or_more)).into(),
+				),
+				None => Exit(Accept),
+			},
+			(11, ret_val) => Exit(ret_val),
+			(12, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [47] [49] [50]
+	/// Start tokens: `(`
+	///
+	/// > [`choice`](https://www.w3.org/TR/xml/#NT-choice) and [`seq`](https://www.w3.org/TR/xml/#NT-seq)
+	/// > are told apart by their first separator, so they are flattened into this production.
+	#[instrument(ret(Debug))]
+	fn children<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"(")? {
+				Some(start) => Yield(CHILDREN_AFTER_GROUP_START, Event::GroupStart(start).into()),
+				None => Exit(Reject),
+			},
+			(CHILDREN_AFTER_GROUP_START, _) => Call!(2, S),
+			(2, _) => Call!(3, cp),
+			(3, Accept) => Call!(4, S),
+			(3, Reject) => Error(Error::Expected48Cp),
+			// A single content particle is a `seq`.
+			(4, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(5, Event::ChoiceSeparator(separator).into())
+				} else if let Some(separator) = buffer.shift_known_array(b",")? {
+					Yield(15, Event::SeqSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(10, Event::GroupEnd(end).into())
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			// [49]
+			(5, _) => Call!(6, S),
+			(6, _) => Call!(7, cp),
+			(7, Accept) => Call!(8, S),
+			(7, Reject) => Error(Error::Expected48Cp),
+			(8, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(5, Event::ChoiceSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(10, Event::GroupEnd(end).into())
+				} else if buffer.shift_known_array(b",")?.is_some() {
+					Error(Error::MixedChoiceAndSeq)
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			// [50]
+			(15, _) => Call!(16, S),
+			(16, _) => Call!(17, cp),
+			(17, Accept) => Call!(18, S),
+			(17, Reject) => Error(Error::Expected48Cp),
+			(18, _) => {
+				if let Some(separator) = buffer.shift_known_array(b",")? {
+					Yield(15, Event::SeqSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(10, Event::GroupEnd(end).into())
+				} else if buffer.shift_known_array(b"|")?.is_some() {
+					Error(Error::MixedChoiceAndSeq)
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			(10, _) => match shift_repetition(buffer)? {
+				Some(repetition) => Yield(11, Event::Repetition(repetition).into()),
+				None => Exit(Accept),
+			},
+			(11, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [48]
+	/// Start tokens: See [`Name`], `(`
+	#[instrument(ret(Debug))]
+	fn cp<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => Call!(1, Name),
+			(1, Accept) => match shift_repetition(buffer)? {
+				Some(repetition) => Yield(2, Event::Repetition(repetition).into()),
+				None => Exit(Accept),
+			},
+			(1, Reject) => Call!(3, children),
+			(2, _) => Exit(Accept),
+			(3, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(3, PEReference),
+			(3, ret_val) => Exit(ret_val),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [52]
@@ -798,6 +1561,7 @@ pub(super) trait Grammar {
 			(2, Accept) => Call!(3, Name),
 			(2, Reject) => Error(Error::Expected3Whitespace),
 			(3, Accept) => Call!(4, AttDef),
+			(3, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(3, PEReference),
 			(3, Reject) => Error(Error::Expected5Name),
 			(4, Accept) => Call!(4, AttDef),
 			(4, Reject) => Call!(5, S),
@@ -812,9 +1576,35 @@ pub(super) trait Grammar {
 	}
 
 	/// [53]
+	/// Start tokens: See [`S`].
+	///
+	/// > Rejects after consuming whitespace if no attribute name follows it.
+	/// > That's fine, as [`AttlistDecl`] allows whitespace before its end anyway.
+	/// >
+	/// > A parameter entity reference in place of the name stands in for whole attribute definitions.
 	#[instrument(ret(Debug))]
 	fn AttDef<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => Call!(1, S),
+			(1, Accept) => CallState!(2, Name, ATTRIBUTE_NAME),
+			(1 | 2, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => {
+				Call!(7, PEReference)
+			}
+			(1 | 2, Reject) => Exit(Reject),
+			(2, Accept) => Call!(3, S),
+			(3, Accept) => Call!(4, AttType),
+			(3, Reject) => Error(Error::Expected3Whitespace),
+			(4, Accept) => Call!(5, S),
+			(4, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(4, PEReference),
+			(4, Reject) => Error(Error::Expected54AttType),
+			(5, Accept) => Call!(6, DefaultDecl),
+			(5, Reject) => Error(Error::Expected3Whitespace),
+			(6 | 7, Accept) => Exit(Accept),
+			(6, Reject) if Self::test_PEReference_in_markupdecl(buffer)? => Call!(6, PEReference),
+			(6, Reject) => Error(Error::Expected60DefaultDecl),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [54]
@@ -901,18 +1691,206 @@ pub(super) trait Grammar {
 
 	/// [58]
 	fn NotationType<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"NOTATION")? {
+				Some(notation) => Yield(1, Event::NOTATION(notation).into()),
+				None => Exit(Reject),
+			},
+			(1, _) => Call!(2, S),
+			(2, Accept) => match buffer.shift_known_array(b"(")? {
+				Some(start) => Yield(3, Event::GroupStart(start).into()),
+				None => Error(Error::ExpectedLiteral(b"(")),
+			},
+			(2, Reject) => Error(Error::Expected3Whitespace),
+			(3, _) => Call!(4, S),
+			(4, _) => Call!(5, Name),
+			(5, Accept) => Call!(6, S),
+			(5, Reject) => Error(Error::Expected5Name),
+			(6, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(3, Event::ChoiceSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(7, Event::GroupEnd(end).into())
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			(7, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [59]
 	fn Enumeration<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"(")? {
+				Some(start) => Yield(1, Event::GroupStart(start).into()),
+				None => Exit(Reject),
+			},
+			(1, _) => Call!(2, S),
+			(2, _) => Call!(3, Nmtoken),
+			(3, Accept) => Call!(4, S),
+			(3, Reject) => Error(Error::Expected7Nmtoken),
+			(4, _) => {
+				if let Some(separator) = buffer.shift_known_array(b"|")? {
+					Yield(1, Event::ChoiceSeparator(separator).into())
+				} else if let Some(end) = buffer.shift_known_array(b")")? {
+					Yield(5, Event::GroupEnd(end).into())
+				} else {
+					Error(Error::ExpectedGroupSeparatorOrEnd)
+				}
+			}
+			(5, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [60]
+	/// Start tokens: `#REQUIRED`, `#IMPLIED`, `#FIXED`, `'`, `"`
+	#[instrument(ret(Debug))]
+	fn DefaultDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(required) = buffer.shift_known_array(b"#REQUIRED")? {
+					Yield(
+						4,
+						Event::DefaultDecl(DefaultDecl::REQUIRED(required)).into(),
+					)
+				} else if let Some(implied) = buffer.shift_known_array(b"#IMPLIED")? {
+					Yield(4, Event::DefaultDecl(DefaultDecl::IMPLIED(implied)).into())
+				} else if let Some(fixed) = buffer.shift_known_array(b"#FIXED")? {
+					Yield(1, Event::DefaultDecl(DefaultDecl::FIXED(fixed)).into())
+				} else {
+					Call!(3, AttValue)
+				}
+			}
+			(1, _) => Call!(2, S),
+			(2, Accept) => Call!(5, AttValue),
+			(2, Reject) => Error(Error::Expected3Whitespace),
+			(3, ret_val) => Exit(ret_val),
+			(4, _) | (5, Accept) => Exit(Accept),
+			(5, Reject) => Error(Error::Expected10AttValue),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
-	/// [61]
+	/// [61] [62] [63]
+	/// Start tokens: `<![`
+	///
+	/// > The keyword may be a parameter entity reference instead, as in `<![%draft;[`.
+	/// > Its replacement text must be scanned right after it, and is expected to contain the keyword.
 	#[instrument(ret(Debug))]
 	fn conditionalSect<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"<![")? {
+				Some(start) => Yield(1, Event::ConditionalSectStart(start).into()),
+				None => Exit(Reject),
+			},
+			(1, _) => Call!(2, S),
+			(2, _) => {
+				if let Some(include) = buffer.shift_known_array(b"INCLUDE")? {
+					Yield(3, Event::INCLUDE(include).into())
+				} else if let Some(ignore) = buffer.shift_known_array(b"IGNORE")? {
+					Yield(6, Event::IGNORE(ignore).into())
+				} else if buffer.filled().starts_with(b"%") {
+					Call!(1, PEReference)
+				} else {
+					Error(Error::ExpectedINCLUDEorIGNORE)
+				}
+			}
+			(3, _) => Call!(4, S),
+			(4, _) => match buffer.shift_known_array(b"[")? {
+				Some(_) => Call!(5, extSubsetDecl),
+				None => Error(Error::ExpectedLiteral(b"[")),
+			},
+			(5, _) => match buffer.shift_known_array(b"]]>")? {
+				Some(end) => Yield(9, Event::ConditionalSectEnd(end).into()),
+				None => Error(Error::ExpectedConditionalSectEnd),
+			},
+			(6, _) => Call!(7, S),
+			(7, _) => match buffer.shift_known_array(b"[")? {
+				Some(_) => Call!(8, ignoreSectContents),
+				None => Error(Error::ExpectedLiteral(b"[")),
+			},
+			(8, _) => match buffer.shift_known_array(b"]]>")? {
+				Some(end) => Yield(9, Event::ConditionalSectEnd(end).into()),
+				None => unreachable!("`ignoreSectContents` only stops in front of `]]>`."),
+			},
+			(9, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [64] [65]
+	///
+	/// > Stops in front of the `]]>` that isn't balanced by a nested `<![`.
+	#[instrument(ret(Debug))]
+	fn ignoreSectContents<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => {
+				match buffer.shift_chars_while(
+					|c| c != '<' && c != ']' && Self::test_Char_minus_RestrictedChar(c),
+					Self::LINE_ENDINGS,
+				)? {
+					Ok(chunk) if !chunk.is_empty() => {
+						Yield(0, Event::IgnoreSectChunk(chunk).into())
+					}
+					Ok(_) => Continue(1),
+					Err(error) => Error(Error::Utf8Error(error)),
+				}
+			}
+			(1, _) => match buffer.filled() {
+				[b'<', b'!', b'[', ..] => Yield(
+					2,
+					Event::IgnoreSectChunk(ascii_str(
+						buffer
+							.shift_known_array(b"<![")
+							.expect("unreachable")
+							.expect("unreachable"),
+					))
+					.into(),
+				),
+				[b']', b']', b'>', ..] => Exit(Accept),
+				b"<" | b"<!" | b"]" | b"]]" => return Err(MoreInputRequired::new()),
+				[b'<', ..] => Yield(
+					0,
+					Event::IgnoreSectChunk(ascii_str(
+						buffer
+							.shift_known_array(b"<")
+							.expect("unreachable")
+							.expect("unreachable"),
+					))
+					.into(),
+				),
+				[b']', ..] => Yield(
+					0,
+					Event::IgnoreSectChunk(ascii_str(
+						buffer
+							.shift_known_array(b"]")
+							.expect("unreachable")
+							.expect("unreachable"),
+					))
+					.into(),
+				),
+				_ => Error(Self::Char_error(buffer)?),
+			},
+			(2, _) => Call!(3, ignoreSectContents),
+			(3, _) => Yield(
+				0,
+				Event::IgnoreSectChunk(ascii_str(
+					buffer
+						.shift_known_array(b"]]>")?
+						.expect("`ignoreSectContents` only stops in front of `]]>`."),
+				))
+				.into(),
+			),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [66]
@@ -954,7 +1932,7 @@ pub(super) trait Grammar {
 				chunk => Yield(4, Event::CharRefHexadecimalChunk(chunk).into()),
 			},
 			(5, _) => match buffer.shift_known_array(b";")? {
-				Some(end) => Yield(6, Event::CharRefEnd(end).into()),
+				Some(end) => Yield(6, Event_::CharRefEnd(end, Self::test_Char)),
 				None => Error(Error::ExpectedLiteral(b";")),
 			},
 			(6, _) => Exit(Accept),
@@ -1021,25 +1999,206 @@ pub(super) trait Grammar {
 		.pipe(Ok)
 	}
 
-	/// [70]
+	/// [70] [71] [72] [73] [74] [76]
+	/// Start tokens: `<!ENTITY`
+	///
+	/// > [`GEDecl`](https://www.w3.org/TR/xml/#NT-GEDecl) and [`PEDecl`](https://www.w3.org/TR/xml/#NT-PEDecl)
+	/// > only differ after their shared start, so they are flattened into this production.
 	#[instrument(ret(Debug))]
 	fn EntityDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"<!ENTITY")? {
+				Some(start) => Yield(1, Event::EntityDeclStart(start).into()),
+				None => Exit(Reject),
+			},
+			(1, _) => Call!(2, S),
+			(2, Accept) => match buffer.shift_known_array(b"%")? {
+				Some(marker) => Yield(3, Event::PEDeclMarker(marker).into()),
+				None => Continue(5),
+			},
+			(2, Reject) => Error(Error::Expected3Whitespace),
+			(3, _) => Call!(4, S),
+			(4, Accept) => Call!(14, Name),
+			(4, Reject) => Error(Error::Expected3Whitespace),
+
+			// [71] [73] [76]
+			(5, _) => Call!(6, Name),
+			(6, Accept) => Call!(7, S),
+			(6, Reject) => Error(Error::Expected5Name),
+			(7, Accept) => Call!(8, EntityValue),
+			(7, Reject) => Error(Error::Expected3Whitespace),
+			(8, Accept) => Call!(20, S),
+			(8, Reject) => Call!(9, ExternalID),
+			(9, Accept) => Call!(10, S),
+			(9, Reject) => Error(Error::Expected73EntityDef),
+			(10, Accept) => match buffer.shift_known_array(b"NDATA")? {
+				Some(ndata) => Yield(11, Event::NDATA(ndata).into()),
+				None => Continue(20),
+			},
+			(10, Reject) => Continue(20),
+			(11, _) => Call!(12, S),
+			(12, Accept) => Call!(13, Name),
+			(12, Reject) => Error(Error::Expected3Whitespace),
+			(13, Accept) => Call!(20, S),
+			(13, Reject) => Error(Error::Expected5Name),
+
+			// [72] [74]
+			(14, Accept) => Call!(15, S),
+			(14, Reject) => Error(Error::Expected5Name),
+			(15, Accept) => Call!(16, EntityValue),
+			(15, Reject) => Error(Error::Expected3Whitespace),
+			(16, Accept) => Call!(20, S),
+			(16, Reject) => Call!(17, ExternalID),
+			(17, Accept) => Call!(20, S),
+			(17, Reject) => Error(Error::Expected74PEDef),
+
+			(20, _) => match buffer.shift_known_array(b">")? {
+				Some(end) => Yield(21, Event::EntityDeclEnd(end).into()),
+				None => Error(Error::ExpectedEntityDeclEnd),
+			},
+			(21, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [75]
+	/// Start tokens: `SYSTEM`, `PUBLIC`
 	#[instrument(ret(Debug))]
 	fn ExternalID<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => {
+				if let Some(system) = buffer.shift_known_array(b"SYSTEM")? {
+					Yield(1, Event::SYSTEM(system).into())
+				} else if let Some(public) = buffer.shift_known_array(b"PUBLIC")? {
+					Yield(4, Event::PUBLIC(public).into())
+				} else {
+					Exit(Reject)
+				}
+			}
+			(1, _) => Call!(2, S),
+			(2, Accept) => Call!(3, SystemLiteral),
+			(2, Reject) => Error(Error::Expected3Whitespace),
+			(3, Accept) => Exit(Accept),
+			(3, Reject) => Error(Error::Expected11SystemLiteral),
+			(4, _) => Call!(5, S),
+			(5, Accept) => Call!(6, PubidLiteral),
+			(5, Reject) => Error(Error::Expected3Whitespace),
+			(6, Accept) => Call!(7, S),
+			(6, Reject) => Error(Error::Expected12PubidLiteral),
+			(7, Accept) => Call!(3, SystemLiteral),
+			(7, Reject) => Error(Error::Expected3Whitespace),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
 	/// [77]
+	/// Start tokens: `<?xml` followed by [`S`]
 	#[instrument(ret(Debug))]
 	fn TextDecl<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
-		todo!()
+		match (state, ret_val) {
+			(0, _) => match buffer.filled() {
+				// Anything else is a processing instruction (or not well-formed).
+				[b'<', b'?', b'x', b'm', b'l', 0x20 | 0x9 | 0xD | 0xA, ..] => Yield(
+					1,
+					Event::TextDeclStart(
+						buffer
+							.shift_known_array(b"<?xml")
+							.expect("unreachable")
+							.expect("unreachable"),
+					)
+					.into(),
+				),
+				filled if b"<?xml".starts_with(filled) => return Err(MoreInputRequired::new()),
+				_ => Exit(Reject),
+			},
+			(1, _) => Call!(2, S_in_XMLDecl),
+			(2, Accept) => Call!(3, VersionInfo_in_TextDecl_minus_initial_S),
+			(2, Reject) => unreachable!("`<?xml` was followed by whitespace."),
+			(3, Accept) => Call!(4, S_in_XMLDecl),
+			(3, Reject) => Call!(5, EncodingDecl_minus_initial_S),
+			(4, Accept) => Call!(5, EncodingDecl_minus_initial_S),
+			(4 | 5, Reject) => Error(Error::Expected80EncodingDecl),
+			(5, Accept) => Call!(6, S_in_XMLDecl),
+			(6, _) => match buffer.shift_known_array(b"?>")? {
+				Some(end) => Yield(7, Event::TextDeclEnd(end).into()),
+				None => Error(Error::ExpectedTextDeclEnd),
+			},
+			(7, _) => Exit(Accept),
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
 	}
 
-	/// [80]
+	/// [24] [26]
+	/// Start tokens: `version`
+	///
+	/// > The version of an external entity doesn't change how it is scanned,
+	/// > so unlike [`VersionInfo`] this never confirms or downgrades the grammar.
+	#[instrument(ret(Debug))]
+	fn VersionInfo_in_TextDecl_minus_initial_S<'a>(
+		buffer: &mut StrBuf<'a>,
+		state: u8,
+		ret_val: RetVal,
+	) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => match buffer.shift_known_array(b"version")? {
+				Some(_) => Call!(1, Eq_in_XMLDecl),
+				None => Exit(Reject),
+			},
+			(1, Accept) => match buffer.shift_known_array(b"'")? {
+				Some(_) => Continue(2),
+				None => match buffer.shift_known_array(b"\"")? {
+					Some(_) => Continue(12),
+					None => Error(Error::ExpectedQuote),
+				},
+			},
+			(1, Reject) => unreachable!("`Eq` shouldn't fail."),
+			(2 | 12, _) => match buffer.shift_known_array(b"1.")? {
+				Some(version) => Yield(state + 1, Event::VersionChunk(version).into()),
+				None => Error(Error::UnsupportedXmlVersion),
+			},
+			(3 | 13, _) => match buffer.shift_bytes_while(|b| b.is_ascii_digit())? {
+				[] => Error(Error::ExpectedDecimalDigit),
+				chunk => Yield(state + 1, Event::VersionChunk(chunk).into()),
+			},
+			(4 | 14, _) => match buffer.shift_bytes_while(|b| b.is_ascii_digit())? {
+				[] => Continue(state + 1),
+				chunk => Yield(state, Event::VersionChunk(chunk).into()),
+			},
+			(5, _) => match buffer.shift_known_array(b"'")? {
+				Some(_) => Exit(Accept),
+				None => Error(Error::ExpectedLiteral(b"'")),
+			},
+			(15, _) => match buffer.shift_known_array(b"\"")? {
+				Some(_) => Exit(Accept),
+				None => Error(Error::ExpectedLiteral(b"\"")),
+			},
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [78]
+	///
+	/// > An entity can't close elements it didn't open, so [`content`] must only stop at the end of input.
+	#[instrument(ret(Debug))]
+	fn extParsedEnt<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
+		match (state, ret_val) {
+			(0, _) => Call!(1, TextDecl),
+			(1, _) => Call!(2, content),
+			(2, _) => match buffer.filled() {
+				[b'<', b'/', ..] => Error(Error::UnexpectedEndTag),
+				_ => Error(Error::Expected43Content),
+			},
+			_ => unreachable!(),
+		}
+		.pipe(Ok)
+	}
+
+	/// [80] [81]
+	/// Start tokens: `encoding`
 	#[instrument(ret(Debug))]
 	fn EncodingDecl_minus_initial_S<'a>(
 		buffer: &mut StrBuf<'a>,
@@ -1048,11 +2207,48 @@ pub(super) trait Grammar {
 	) -> NextFnR<'a> {
 		match (state, ret_val) {
 			(0, _) => match buffer.shift_known_array(b"encoding")? {
-				Some(_) => Call!(1, Eq),
+				Some(_) => Call!(1, Eq_in_XMLDecl),
 				None => Exit(Reject),
 			},
-			(1, Accept) => todo!(),
+			(1, Accept) => match buffer.shift_known_array(b"'")? {
+				Some(_) => Continue(2),
+				None => match buffer.shift_known_array(b"\"")? {
+					Some(_) => Continue(5),
+					None => Error(Error::ExpectedQuote),
+				},
+			},
 			(1, Reject) => Error(Error::Expected25Eq),
+			// [81]
+			(2 | 5, _) => match buffer.filled() {
+				[b'A'..=b'Z' | b'a'..=b'z', ..] => Yield(
+					state + 1,
+					Event::EncNameChunk(
+						buffer
+							.shift_bytes_while(|b| {
+								b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')
+							})
+							.expect("unreachable"),
+					)
+					.into(),
+				),
+				[] => return Err(MoreInputRequired::new()),
+				_ => Error(Error::Expected81EncName),
+			},
+			(3 | 6, _) => match buffer.shift_bytes_while(|b| {
+				b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')
+			})? {
+				[] => Continue(state + 1),
+				chunk => Yield(state, Event::EncNameChunk(chunk).into()),
+			},
+			(4, _) => match buffer.shift_known_array(b"'")? {
+				Some(end) => Yield(8, Event::EncNameEnd(end).into()),
+				None => Error(Error::ExpectedLiteral(b"'")),
+			},
+			(7, _) => match buffer.shift_known_array(b"\"")? {
+				Some(end) => Yield(8, Event::EncNameEnd(end).into()),
+				None => Error(Error::ExpectedLiteral(b"\"")),
+			},
+			(8, _) => Exit(Accept),
 			_ => unreachable!(),
 		}
 		.pipe(Ok)
@@ -1076,7 +2272,7 @@ pub(super) trait Grammar {
 				if let Some(system) = buffer.shift_known_array(b"SYSTEM")? {
 					Yield(5, Event::SYSTEM(system).into())
 				} else if let Some(public) = buffer.shift_known_array(b"PUBLIC")? {
-					Yield(todo!(), Event::PUBLIC(public).into())
+					Yield(7, Event::PUBLIC(public).into())
 				} else {
 					Error(Error::ExpectedSYSTEMorPUBLIC)
 				}
@@ -1093,7 +2289,7 @@ pub(super) trait Grammar {
 			(9, Accept) => Call!(10, S),
 			(9, Reject) => Error(Error::Expected12PubidLiteral),
 			(10, Accept) => Call!(11, SystemLiteral),
-			(11, Reject) => Continue(12),
+			(10 | 11, Reject) => Continue(12),
 			(11, Accept) => Call!(12, S),
 			(12, _) => match buffer.shift_known_array(b">")? {
 				Some(end) => Yield(13, Event::NotationDeclEnd(end).into()),
@@ -7,7 +7,10 @@ use super::{
 	NextFnR,
 	RetVal::{self, *},
 };
-use crate::{buffer::StrBuf, scanner::MoreInputRequired};
+use crate::{
+	buffer::{LineEndings, StrBuf},
+	scanner::MoreInputRequired,
+};
 use std::any::type_name;
 use tap::Pipe;
 use tracing::instrument;
@@ -30,6 +33,9 @@ macro_rules! Call {
 pub(super) enum Xml1_1 {}
 
 impl Grammar for Xml1_1 {
+	/// Also NEL and LS.
+	const LINE_ENDINGS: LineEndings = LineEndings::Xml1_1;
+
 	// [1] `document` unmodified.
 
 	/// [2]
@@ -40,7 +46,16 @@ impl Grammar for Xml1_1 {
 		)
 	}
 
-	//TODO: [2a] `RestrictedChar` added!
+	/// [2a]
+	fn test_RestrictedChar(c: char) -> bool {
+		matches!(c,
+			| '\u{1}'..='\u{8}'
+			| '\u{B}'..='\u{C}'
+			| '\u{E}'..='\u{1F}'
+			| '\u{7F}'..='\u{84}'
+			| '\u{86}'..='\u{9F}'
+		)
+	}
 
 	// [3] `S` unmodified.
 
@@ -71,13 +86,13 @@ impl Grammar for Xml1_1 {
 	#[instrument(ret(Debug))]
 	fn VersionInfo<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			(0, _) => Call!(1, S),
+			(0, _) => Call!(1, S_in_XMLDecl),
 			(1, Accept) => match buffer.shift_known_array(b"version")? {
 				Some(_) => Continue(2),
 				None => Error(Error::ExpectedLiteral(b"version")),
 			},
 			(1, Reject) => Exit(Reject),
-			(2, _) => Call!(3, Eq),
+			(2, _) => Call!(3, Eq_in_XMLDecl),
 			(3, Accept) => match buffer.shift_known_array(b"'")? {
 				Some(_) => Continue(4),
 				None => match buffer.shift_known_array(b"\"")? {
@@ -107,26 +122,26 @@ impl Grammar for Xml1_1 {
 	// [25] `Eq` unmodified.
 
 	/// [26]
-	/// Must be `1.1` now (and check for termination).
+	/// Must be `1.1` now, directly followed by the closing quote.
 	#[instrument(ret(Debug))]
 	fn VersionNum<'a>(buffer: &mut StrBuf<'a>, state: u8, ret_val: RetVal) -> NextFnR<'a> {
 		match (state, ret_val) {
-			//BUG: Ensure this is terminated!
 			(0, _) => match buffer.filled() {
-				[b'1', b'.', b'1', x, ..] if !(b'0'..=b'9').contains(x) => Yield(
-					1,
-					Event::VersionChunk(
-						buffer
-							.shift_known_array(b"1.1")
-							.expect("unreachable")
-							.expect("unreachable"),
-					)
-					.into(),
-				),
-				b"1.1" | b"1." | b"1" => return Err(MoreInputRequired::new()),
+				[b'1', b'.', b'1', b'\'' | b'"', ..] => Yield(1, Event_::ConfirmVersion1_1),
+				b"1.1" | b"1." | b"1" | [] => return Err(MoreInputRequired::new()),
 				_ => Exit(Reject),
 			},
-			(1, _) => Exit(Accept),
+			(1, _) => Yield(
+				2,
+				Event::VersionChunk(
+					buffer
+						.shift_known_array(b"1.1")
+						.expect("unreachable")
+						.expect("unreachable"),
+				)
+				.into(),
+			),
+			(2, _) => Exit(Accept),
 			_ => unreachable!(),
 		}
 		.pipe(Ok)
@@ -142,9 +157,25 @@ impl Grammar for Xml1_1 {
 	// through
 	// [77] `TextDecl` unmodified.
 
-	//TODO: [78] `extParsedEnt` modified!
+	// [78] `extParsedEnt` unmodified. (Its `RestrictedChar` exclusion is the same as `document`'s.)
 
 	// [80] `EncodingDecl` unmodified.
 	// through
 	// [83] `PublicID` unmodified.
 }
+
+/// [`Xml1_1`], but for the external subset, where parameter entity references may appear within markup declarations.
+pub(super) enum Xml1_1ExtSubset {}
+
+impl Grammar for Xml1_1ExtSubset {
+	const LINE_ENDINGS: LineEndings = Xml1_1::LINE_ENDINGS;
+	const PE_REFERENCES_IN_MARKUPDECL: bool = true;
+
+	fn test_Char(c: char) -> bool {
+		Xml1_1::test_Char(c)
+	}
+
+	fn test_RestrictedChar(c: char) -> bool {
+		Xml1_1::test_RestrictedChar(c)
+	}
+}
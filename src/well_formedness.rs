@@ -0,0 +1,797 @@
+//! Well-formedness constraints that need more memory than the [`Scanner`] itself.
+
+use crate::{
+	buffer::StrBuf,
+	scanner::{is_name_char, Event, MoreInputRequired, SDDecl, Scanner, ScannerError},
+};
+use std::{
+	cell::Cell,
+	collections::{hash_map::RandomState, HashMap, HashSet},
+};
+
+/// The entities every processor recognizes, whether declared or not.
+const PREDEFINED_ENTITIES: [&str; 5] = ["lt", "gt", "amp", "apos", "quot"];
+
+/// Checks well-formedness constraints on the [`Event`]s of a wrapped [`Scanner`].
+///
+/// Checked are:
+///
+/// - [WFC: Element Type Match](https://www.w3.org/TR/xml11/#GIMatch)
+/// - [WFC: Unique Att Spec](https://www.w3.org/TR/xml11/#uniqattspec)
+/// - [WFC: Entity Declared](https://www.w3.org/TR/xml11/#wf-entdeclared),
+///   but only for standalone documents and those without external subset or parameter entity references.
+///   (Otherwise, undeclared references are left to a validating processor.)
+/// - [WFC: Parsed Entity](https://www.w3.org/TR/xml11/#textent)
+/// - [WFC: No Recursion](https://www.w3.org/TR/xml11/#norecursion)
+/// - [WFC: No External Entity References](https://www.w3.org/TR/xml11/#NoExternalRefs)
+/// - [WFC: No < in Attribute Values](https://www.w3.org/TR/xml11/#CleanAttrVals)
+///
+/// General entities are known from the internal subset only, so references in external entities aren't followed.
+/// As per [5.1](https://www.w3.org/TR/xml11/#proc-types), declarations after the first parameter entity reference aren't processed either,
+/// unless the document is standalone.
+#[derive(Debug)]
+pub struct Checker {
+	scanner: Scanner,
+	name_budget: usize,
+	/// The names of all open elements, back to back.
+	open_element_names: String,
+	/// Where each name in [`Self::open_element_names`] starts.
+	open_element_starts: Vec<usize>,
+	end_tag_name: String,
+	attribute_limit: usize,
+	/// The names of the attributes specified so far in the current start tag.
+	///
+	/// [`RandomState`] keys the hashes per instance, so colliding names can't be prepared in advance.
+	attribute_names: HashSet<Box<str>, RandomState>,
+	attribute_names_len: usize,
+	attribute_name: String,
+	in_start_tag: bool,
+	/// The general entities declared so far, by name. The first declaration is binding.
+	entities: HashMap<Box<str>, Entity, RandomState>,
+	/// The length of all names stored in [`Self::entities`].
+	entities_len: usize,
+	entity_declaration: Option<EntityDeclaration>,
+	reference_name: String,
+	markup_declaration: MarkupDeclaration,
+	in_att_value: bool,
+	declarations: Declarations,
+	/// Whether [`Self::reference_name`] was just checked outside of an entity value.
+	checked_reference: bool,
+	/// How many bytes of replacement text to keep, if any.
+	replacement_text_budget: Option<usize>,
+	replacement_texts_len: usize,
+	collecting: Collecting,
+}
+
+/// Where in the document type declaration the scanner is, as far as `SYSTEM` and `PUBLIC` are concerned.
+#[derive(Debug, PartialEq, Eq)]
+enum MarkupDeclaration {
+	None,
+	Doctypedecl,
+	NotationDecl,
+}
+
+/// What the document says about where its declarations are.
+#[derive(Debug, Default)]
+struct Declarations {
+	standalone: bool,
+	has_external_subset: bool,
+	has_parameter_entity_references: bool,
+}
+
+impl Declarations {
+	/// Whether all declarations are known, so that [WFC: Entity Declared](https://www.w3.org/TR/xml11/#wf-entdeclared) applies.
+	fn undeclared_entity_is_error(&self) -> bool {
+		self.standalone || !(self.has_external_subset || self.has_parameter_entity_references)
+	}
+
+	/// Whether entity declarations are processed, which stops at the first (unread) parameter entity reference.
+	fn processes_entity_declarations(&self) -> bool {
+		self.standalone || !self.has_parameter_entity_references
+	}
+}
+
+#[derive(Debug)]
+struct Entity {
+	kind: EntityKind,
+	/// Whether references to this entity were found well-formed in content, so it doesn't need to be walked again.
+	verified_in_content: Cell<bool>,
+	/// Like [`Self::verified_in_content`], but for attribute values.
+	verified_in_att_value: Cell<bool>,
+}
+
+impl Entity {
+	fn verified(&self, in_att_value: bool) -> &Cell<bool> {
+		if in_att_value {
+			&self.verified_in_att_value
+		} else {
+			&self.verified_in_content
+		}
+	}
+}
+
+#[derive(Debug)]
+enum EntityKind {
+	Internal {
+		/// The general entities referenced in the replacement text,
+		/// including those only formed by character references in the literal entity value.
+		references: Vec<Box<str>>,
+		/// The name after an `&` at the end of the replacement text so far, which may still become a reference.
+		partial_reference: Option<String>,
+		/// Whether the replacement text contains a `<`, either literally or through a character reference.
+		contains_less_than: bool,
+		/// Empty unless replacement texts are kept.
+		replacement_text: String,
+	},
+	ExternalParsed {
+		/// Empty unless replacement texts are kept.
+		public_id: Option<String>,
+		/// Empty unless replacement texts are kept.
+		system_id: String,
+	},
+	Unparsed,
+}
+
+impl EntityKind {
+	/// How much of the replacement text budget this entity uses.
+	fn kept_len(&self) -> usize {
+		match self {
+			EntityKind::Internal {
+				replacement_text, ..
+			} => replacement_text.len(),
+			EntityKind::ExternalParsed {
+				public_id,
+				system_id,
+			} => public_id.as_ref().map_or(0, String::len) + system_id.len(),
+			EntityKind::Unparsed => 0,
+		}
+	}
+}
+
+#[derive(Debug)]
+struct EntityDeclaration {
+	name: String,
+	is_parameter_entity: bool,
+	kind: EntityKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Collecting {
+	Nothing,
+	StartTagName,
+	EndTagName,
+	AttributeName,
+	EntityName,
+	ReferenceName,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WellFormednessError {
+	ScannerError(ScannerError),
+	/// The name of an end tag doesn't match that of the element it closes.
+	ElementTypeMismatch {
+		start_tag: Box<str>,
+		end_tag: Box<str>,
+	},
+	/// An attribute is specified more than once in the same start tag.
+	DuplicateAttribute(Box<str>),
+	/// A start tag specifies more attributes than the attribute limit.
+	AttributeLimitExceeded,
+	/// The names of the open elements (and of the tag being scanned, including its attributes)
+	/// or of declared and referenced entities are longer than the name budget in bytes.
+	NameBudgetExceeded,
+	/// An entity is referenced but not declared, in a document where that is a well-formedness error.
+	UndeclaredEntity(Box<str>),
+	/// An unparsed entity is referenced (directly or indirectly).
+	UnparsedEntityReference(Box<str>),
+	/// An entity's replacement text references that entity again (directly or indirectly).
+	RecursiveEntityReference(Box<str>),
+	/// An attribute value references an external entity (directly or indirectly).
+	ExternalEntityReferenceInAttributeValue(Box<str>),
+	/// An attribute value references (directly or indirectly) an entity whose replacement text contains a `<`.
+	LessThanInAttributeValue(Box<str>),
+	/// The replacement texts and external identifiers of the declared entities are longer than the replacement text budget in bytes.
+	ReplacementTextBudgetExceeded,
+}
+
+impl From<ScannerError> for WellFormednessError {
+	fn from(scanner_error: ScannerError) -> Self {
+		Self::ScannerError(scanner_error)
+	}
+}
+
+impl Checker {
+	/// Wraps `scanner`, tracking at most `name_budget` bytes of element and entity names at a time.
+	#[must_use]
+	pub fn new(scanner: Scanner, name_budget: usize) -> Self {
+		Self {
+			scanner,
+			name_budget,
+			open_element_names: String::new(),
+			open_element_starts: Vec::new(),
+			end_tag_name: String::new(),
+			attribute_limit: usize::MAX,
+			attribute_names: HashSet::default(),
+			attribute_names_len: 0,
+			attribute_name: String::new(),
+			in_start_tag: false,
+			entities: HashMap::default(),
+			entities_len: 0,
+			entity_declaration: None,
+			reference_name: String::new(),
+			markup_declaration: MarkupDeclaration::None,
+			in_att_value: false,
+			declarations: Declarations::default(),
+			checked_reference: false,
+			replacement_text_budget: None,
+			replacement_texts_len: 0,
+			collecting: Collecting::Nothing,
+		}
+	}
+
+	/// Sets how many attributes a start tag may specify.
+	///
+	/// Defaults to no limit other than the name budget.
+	#[must_use]
+	pub fn with_attribute_limit(mut self, attribute_limit: usize) -> Self {
+		self.attribute_limit = attribute_limit;
+		self
+	}
+
+	/// Like [`Scanner::resume`], but with errors for events that violate a checked constraint.
+	///
+	/// # Errors
+	///
+	/// Iff the wrapped [`Scanner`] requires more input.
+	///
+	/// The inner [`Result`] is an [`Err`] for any scanner error and any violated constraint.
+	pub fn resume<'a>(
+		&mut self,
+		buffer: &mut StrBuf<'a>,
+	) -> Result<Result<Option<Event<'a>>, WellFormednessError>, MoreInputRequired> {
+		let event = match self.scanner.resume(buffer)? {
+			Ok(Some(event)) => event,
+			Ok(None) => return Ok(Ok(None)),
+			Err(error) => return Ok(Err(error.into())),
+		};
+		Ok(self.check(&event).map(|()| Some(event)))
+	}
+
+	/// Keeps the replacement texts of internal general entities and the external identifiers of external ones, up to `budget` bytes in total.
+	pub(crate) fn keep_replacement_texts(&mut self, budget: usize) {
+		self.replacement_text_budget = Some(budget);
+	}
+
+	/// The replacement text of a predefined or declared internal general entity.
+	///
+	/// Only available for declared entities if replacement texts are kept.
+	pub(crate) fn replacement_text(&self, name: &str) -> Option<&str> {
+		match name {
+			"lt" => Some("&#60;"),
+			"gt" => Some(">"),
+			"amp" => Some("&#38;"),
+			"apos" => Some("'"),
+			"quot" => Some("\""),
+			_ => match self.entities.get(name) {
+				Some(Entity {
+					kind: EntityKind::Internal {
+						replacement_text, ..
+					},
+					..
+				}) if self.replacement_text_budget.is_some() => Some(replacement_text),
+				_ => None,
+			},
+		}
+	}
+
+	/// The public ID (if any) and system ID of a declared external parsed entity.
+	///
+	/// Only available if replacement texts are kept.
+	pub(crate) fn external_id(&self, name: &str) -> Option<(Option<&str>, &str)> {
+		match self.entities.get(name) {
+			Some(Entity {
+				kind: EntityKind::ExternalParsed {
+					public_id,
+					system_id,
+				},
+				..
+			}) if self.replacement_text_budget.is_some() => Some((public_id.as_deref(), system_id)),
+			_ => None,
+		}
+	}
+
+	/// The name of the entity reference that ended with the last event, unless it was bypassed,
+	/// and whether it's in an attribute value.
+	pub(crate) fn checked_reference(&self) -> Option<(&str, bool)> {
+		self.checked_reference
+			.then_some((self.reference_name.as_str(), self.in_att_value))
+	}
+
+	pub(crate) fn scanner(&self) -> &Scanner {
+		&self.scanner
+	}
+
+	pub(crate) fn open_element_count(&self) -> usize {
+		self.open_element_starts.len()
+	}
+
+	fn check(&mut self, event: &Event) -> Result<(), WellFormednessError> {
+		self.checked_reference = false;
+		self.collecting = match event {
+			Event::StartTagStart(_) => {
+				self.open_element_starts.push(self.open_element_names.len());
+				self.in_start_tag = true;
+				Collecting::StartTagName
+			}
+			Event::EndTagStart(_) => {
+				self.end_tag_name.clear();
+				Collecting::EndTagName
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::StartTagName => {
+				self.spend_name_budget(chunk.len())?;
+				self.open_element_names.push_str(chunk);
+				Collecting::StartTagName
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::EndTagName => {
+				self.spend_name_budget(chunk.len())?;
+				self.end_tag_name.push_str(chunk);
+				Collecting::EndTagName
+			}
+			Event::AttributeNameChunk(chunk) if self.in_start_tag => {
+				if self.collecting != Collecting::AttributeName {
+					self.attribute_name.clear();
+				}
+				self.spend_name_budget(chunk.len())?;
+				self.attribute_name.push_str(chunk);
+				Collecting::AttributeName
+			}
+			Event::AttValueStart(_) => {
+				if self.in_start_tag {
+					self.specify_attribute()?;
+				}
+				self.in_att_value = true;
+				Collecting::Nothing
+			}
+			Event::AttValueEnd(_) => {
+				self.in_att_value = false;
+				Collecting::Nothing
+			}
+			Event::StartTagEnd(_) => {
+				self.end_start_tag();
+				Collecting::Nothing
+			}
+			Event::StartTagEndEmpty(_) => {
+				self.end_start_tag();
+				self.pop_open_element();
+				Collecting::Nothing
+			}
+			Event::EndTagEnd(_) => {
+				let start = *self.open_element_starts.last().expect("unreachable");
+				if self.open_element_names[start..] != self.end_tag_name {
+					return Err(WellFormednessError::ElementTypeMismatch {
+						start_tag: self.open_element_names[start..].into(),
+						end_tag: self.end_tag_name.as_str().into(),
+					});
+				}
+				self.pop_open_element();
+				Collecting::Nothing
+			}
+			_ => self.check_entities(event)?,
+		};
+		Ok(())
+	}
+
+	fn check_entities(&mut self, event: &Event) -> Result<Collecting, WellFormednessError> {
+		Ok(match event {
+			Event::SDDecl(SDDecl::Yes(_)) => {
+				self.declarations.standalone = true;
+				Collecting::Nothing
+			}
+			Event::DoctypedeclStart(_) | Event::NotationDeclEnd(_) => {
+				self.markup_declaration = MarkupDeclaration::Doctypedecl;
+				Collecting::Nothing
+			}
+			Event::DoctypedeclEnd(_) => {
+				self.markup_declaration = MarkupDeclaration::None;
+				Collecting::Nothing
+			}
+			Event::NotationDeclStart(_) => {
+				self.markup_declaration = MarkupDeclaration::NotationDecl;
+				Collecting::Nothing
+			}
+			Event::PEReferenceStart(_) => {
+				self.declarations.has_parameter_entity_references = true;
+				Collecting::Nothing
+			}
+			Event::EntityDeclStart(_) => {
+				self.entity_declaration = Some(EntityDeclaration {
+					name: String::new(),
+					is_parameter_entity: false,
+					kind: EntityKind::Internal {
+						references: Vec::new(),
+						partial_reference: None,
+						contains_less_than: false,
+						replacement_text: String::new(),
+					},
+				});
+				Collecting::EntityName
+			}
+			Event::PEDeclMarker(_) => {
+				if let Some(declaration) = &mut self.entity_declaration {
+					declaration.is_parameter_entity = true;
+				}
+				Collecting::EntityName
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::EntityName => {
+				self.spend_name_budget(chunk.len())?;
+				let declaration = self.entity_declaration.as_mut().expect("unreachable");
+				declaration.name.push_str(chunk);
+				self.entities_len += chunk.len();
+				Collecting::EntityName
+			}
+			Event::EntityValueChunk(chunk) => {
+				if chunk.contains('<') {
+					self.entity_value_contains_less_than();
+				}
+				self.push_replacement_text(chunk)?;
+				Collecting::Nothing
+			}
+			Event::CharRefEnd(_, c) if self.entity_declaration.is_some() => {
+				if *c == '<' {
+					self.entity_value_contains_less_than();
+				}
+				self.push_replacement_text(c.encode_utf8(&mut [0; 4]))?;
+				Collecting::Nothing
+			}
+			Event::NDATA(_) => {
+				if let Some(declaration) = &mut self.entity_declaration {
+					self.replacement_texts_len -= declaration.kind.kept_len();
+					declaration.kind = EntityKind::Unparsed;
+				}
+				Collecting::Nothing
+			}
+			Event::EntityDeclEnd(_) => {
+				self.end_entity_declaration();
+				Collecting::Nothing
+			}
+			Event::EntityRefStart(_) => {
+				self.reference_name.clear();
+				Collecting::ReferenceName
+			}
+			Event::NameChunk(chunk) if self.collecting == Collecting::ReferenceName => {
+				self.spend_name_budget(chunk.len())?;
+				self.reference_name.push_str(chunk);
+				Collecting::ReferenceName
+			}
+			Event::EntityRefEnd(_) => {
+				self.end_reference()?;
+				Collecting::Nothing
+			}
+			_ => {
+				self.collect_external_id(event)?;
+				Collecting::Nothing
+			}
+		})
+	}
+
+	fn collect_external_id(&mut self, event: &Event) -> Result<(), WellFormednessError> {
+		let len = match event {
+			Event::SYSTEM(_) | Event::PUBLIC(_) => {
+				match &mut self.entity_declaration {
+					Some(declaration) => {
+						declaration.kind = EntityKind::ExternalParsed {
+							public_id: None,
+							system_id: String::new(),
+						}
+					}
+					None if self.markup_declaration == MarkupDeclaration::Doctypedecl => {
+						self.declarations.has_external_subset = true;
+					}
+					None => (),
+				}
+				return Ok(());
+			}
+			Event::PubidLiteralChunk(chunk) | Event::SystemLiteralChunk(chunk) => chunk.len(),
+			_ => 0,
+		};
+		if self.spend_external_id_budget(len)? {
+			if let Some(EntityDeclaration {
+				kind: EntityKind::ExternalParsed {
+					public_id,
+					system_id,
+				},
+				..
+			}) = &mut self.entity_declaration
+			{
+				match event {
+					Event::PubidLiteralStart(_) => *public_id = Some(String::new()),
+					Event::PubidLiteralChunk(chunk) => {
+						public_id.get_or_insert_with(String::new).push_str(chunk);
+					}
+					Event::SystemLiteralChunk(chunk) => system_id.push_str(chunk),
+					_ => (),
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn spend_name_budget(&self, len: usize) -> Result<(), WellFormednessError> {
+		if self.open_element_names.len()
+			+ self.end_tag_name.len()
+			+ self.attribute_names_len
+			+ self.attribute_name.len()
+			+ self.entities_len
+			+ self.reference_name.len()
+			+ len > self.name_budget
+		{
+			Err(WellFormednessError::NameBudgetExceeded)
+		} else {
+			Ok(())
+		}
+	}
+
+	fn specify_attribute(&mut self) -> Result<(), WellFormednessError> {
+		if self.attribute_names.len() >= self.attribute_limit {
+			return Err(WellFormednessError::AttributeLimitExceeded);
+		}
+		if self.attribute_names.contains(self.attribute_name.as_str()) {
+			return Err(WellFormednessError::DuplicateAttribute(
+				self.attribute_name.as_str().into(),
+			));
+		}
+		self.attribute_names_len += self.attribute_name.len();
+		self.attribute_names
+			.insert(self.attribute_name.as_str().into());
+		self.attribute_name.clear();
+		Ok(())
+	}
+
+	fn end_start_tag(&mut self) {
+		self.in_start_tag = false;
+		self.attribute_names.clear();
+		self.attribute_names_len = 0;
+	}
+
+	fn pop_open_element(&mut self) {
+		let start = self.open_element_starts.pop().expect("unreachable");
+		self.open_element_names.truncate(start);
+	}
+
+	fn entity_value_contains_less_than(&mut self) {
+		if let Some(EntityDeclaration {
+			kind: EntityKind::Internal {
+				contains_less_than, ..
+			},
+			..
+		}) = &mut self.entity_declaration
+		{
+			*contains_less_than = true;
+		}
+	}
+
+	fn push_replacement_text(&mut self, text: &str) -> Result<(), WellFormednessError> {
+		let Some(EntityDeclaration {
+			kind: EntityKind::Internal {
+				references,
+				partial_reference,
+				..
+			},
+			..
+		}) = &mut self.entity_declaration
+		else {
+			return Ok(());
+		};
+		let names_len = self.entities_len;
+		for c in text.chars() {
+			match partial_reference {
+				None => {
+					if c == '&' {
+						*partial_reference = Some(String::new());
+					}
+				}
+				Some(name) if c == ';' => {
+					// Character references stay unexpanded in replacement text.
+					if !name.is_empty() && !name.starts_with('#') {
+						references.push(name.as_str().into());
+					} else {
+						self.entities_len -= name.len();
+					}
+					*partial_reference = None;
+				}
+				Some(name)
+					if (name.is_empty() && c == '#')
+						|| (name.starts_with('#') && c.is_ascii_alphanumeric())
+						|| (!name.starts_with('#') && is_name_char(c, name.is_empty())) =>
+				{
+					name.push(c);
+					self.entities_len += c.len_utf8();
+				}
+				Some(name) => {
+					// Not a reference, which is left for scanning the replacement text to report.
+					self.entities_len -= name.len();
+					*partial_reference = (c == '&').then(String::new);
+				}
+			}
+		}
+		if self.entities_len > names_len {
+			self.spend_name_budget(0)?;
+		}
+
+		if !self.spend_replacement_text_budget(text.len())? {
+			return Ok(());
+		}
+		if let Some(EntityDeclaration {
+			kind: EntityKind::Internal {
+				replacement_text, ..
+			},
+			..
+		}) = &mut self.entity_declaration
+		{
+			replacement_text.push_str(text);
+		}
+		Ok(())
+	}
+
+	/// Like [`Self::spend_replacement_text_budget`], but `false` outside of external entity declarations.
+	fn spend_external_id_budget(&mut self, len: usize) -> Result<bool, WellFormednessError> {
+		if matches!(
+			self.entity_declaration,
+			Some(EntityDeclaration {
+				kind: EntityKind::ExternalParsed { .. },
+				..
+			})
+		) {
+			self.spend_replacement_text_budget(len)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Returns whether replacement texts (and external identifiers) are kept, after making room for `len` more bytes.
+	fn spend_replacement_text_budget(&mut self, len: usize) -> Result<bool, WellFormednessError> {
+		match self.replacement_text_budget {
+			None => Ok(false),
+			Some(budget) if self.replacement_texts_len + len > budget => {
+				Err(WellFormednessError::ReplacementTextBudgetExceeded)
+			}
+			Some(_) => {
+				self.replacement_texts_len += len;
+				Ok(true)
+			}
+		}
+	}
+
+	fn end_entity_declaration(&mut self) {
+		let declaration = self.entity_declaration.take().expect("unreachable");
+		if declaration.is_parameter_entity
+			|| !self.declarations.processes_entity_declarations()
+			|| self.entities.contains_key(declaration.name.as_str())
+		{
+			// Not kept.
+			self.entities_len -= declaration.name.len();
+			self.replacement_texts_len -= declaration.kind.kept_len();
+			if let EntityKind::Internal {
+				references,
+				partial_reference,
+				..
+			} = declaration.kind
+			{
+				self.entities_len -= references.iter().map(|name| name.len()).sum::<usize>()
+					+ partial_reference.map_or(0, |name| name.len());
+			}
+			return;
+		}
+		let mut kind = declaration.kind;
+		if let EntityKind::Internal {
+			partial_reference, ..
+		} = &mut kind
+		{
+			if let Some(name) = partial_reference.take() {
+				self.entities_len -= name.len();
+			}
+		}
+		self.entities.insert(
+			declaration.name.into(),
+			Entity {
+				kind,
+				verified_in_content: Cell::new(false),
+				verified_in_att_value: Cell::new(false),
+			},
+		);
+	}
+
+	fn end_reference(&mut self) -> Result<(), WellFormednessError> {
+		let name = std::mem::take(&mut self.reference_name);
+		if self.entity_declaration.is_some() {
+			// In entity values, references are bypassed until the entity itself is referenced.
+			self.push_replacement_text("&")?;
+			self.push_replacement_text(&name)?;
+			self.push_replacement_text(";")
+		} else {
+			self.check_reference(&name, self.in_att_value)?;
+			self.reference_name = name;
+			self.checked_reference = true;
+			Ok(())
+		}
+	}
+
+	/// Walks the entities reachable from `name` depth-first.
+	fn check_reference(&self, name: &str, in_att_value: bool) -> Result<(), WellFormednessError> {
+		let undeclared_is_error = self.declarations.undeclared_entity_is_error();
+
+		let mut visited = HashSet::new();
+		let mut all_declared = true;
+		// The internal entities being walked, each with the index of its next reference.
+		let mut path: Vec<(&str, &[Box<str>], usize)> = Vec::new();
+		let mut next = Some(name);
+		loop {
+			if let Some(name) = next.take() {
+				if path.iter().any(|&(open, _, _)| open == name) {
+					return Err(WellFormednessError::RecursiveEntityReference(name.into()));
+				}
+				if visited.insert(name) {
+					match self.entities.get(name) {
+						None if PREDEFINED_ENTITIES.contains(&name) => (),
+						None if undeclared_is_error => {
+							return Err(WellFormednessError::UndeclaredEntity(name.into()))
+						}
+						None => all_declared = false,
+						Some(entity) if entity.verified(in_att_value).get() => (),
+						Some(Entity {
+							kind: EntityKind::Unparsed,
+							..
+						}) => return Err(WellFormednessError::UnparsedEntityReference(name.into())),
+						Some(Entity {
+							kind: EntityKind::ExternalParsed { .. },
+							..
+						}) if in_att_value => {
+							return Err(
+								WellFormednessError::ExternalEntityReferenceInAttributeValue(
+									name.into(),
+								),
+							)
+						}
+						Some(Entity {
+							kind: EntityKind::ExternalParsed { .. },
+							..
+						}) => (),
+						Some(Entity {
+							kind:
+								EntityKind::Internal {
+									contains_less_than: true,
+									..
+								},
+							..
+						}) if in_att_value => return Err(WellFormednessError::LessThanInAttributeValue(name.into())),
+						Some(Entity {
+							kind: EntityKind::Internal { references, .. },
+							..
+						}) => path.push((name, references, 0)),
+					}
+				}
+			}
+
+			match path.last_mut() {
+				Some((_, references, index)) => match references.get(*index) {
+					Some(reference) => {
+						*index += 1;
+						next = Some(reference);
+					}
+					None => drop(path.pop()),
+				},
+				None => break,
+			}
+		}
+
+		// Undeclared entities may still be declared later in the internal subset.
+		if all_declared {
+			for name in visited {
+				if let Some(entity) = self.entities.get(name) {
+					entity.verified(in_att_value).set(true);
+				}
+			}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,27 @@
+use std::mem::MaybeUninit;
+use yolo_xml::buffer::StrBuf;
+
+#[test]
+fn unshift_reset_full() {
+	let mut memory = [MaybeUninit::uninit(); 4];
+	let mut buffer = StrBuf::new(&mut memory);
+	fill(&mut buffer, b"abcd");
+
+	buffer.shift_filled(2).unwrap();
+	assert_eq!(unsafe { buffer.unshift_reset() }, 2);
+	assert!(buffer.is_at_origin());
+	assert_eq!(buffer.filled(), b"cd");
+
+	fill(&mut buffer, b"ef");
+	buffer.shift_filled(4).unwrap();
+	assert_eq!(unsafe { buffer.unshift_reset() }, 4);
+	assert!(buffer.is_at_origin());
+	assert_eq!(buffer.filled(), b"");
+}
+
+fn fill(buffer: &mut StrBuf, data: &[u8]) {
+	for (slot, byte) in buffer.remaining_maybe_uninitialized().iter_mut().zip(data) {
+		slot.write(*byte);
+	}
+	unsafe { buffer.assume_filled_n_remaining(data.len()) };
+}
@@ -0,0 +1,387 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+use yolo_xml::resolver::{
+	catalog::{CatalogError, CatalogResolver, Prefer},
+	EntityResolver, EntitySource, Refusal,
+};
+
+const DOCBOOK: &str = "-//OASIS//DTD DocBook XML V4.5//EN";
+const XHTML: &str = "-//W3C//DTD XHTML 1.0 Strict//EN";
+
+#[test]
+fn public_and_system() {
+	let dir = files(
+		"public_and_system",
+		&[(
+			"catalog.xml",
+			&catalog(&format!(
+				"<public publicId='{}' uri='docbook/docbookx.dtd'/>
+				<system systemId='http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd' uri='xhtml/xhtml1-strict.dtd'/>",
+				DOCBOOK
+			)),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	assert_eq!(
+		resolver.resolve(
+			Some(DOCBOOK),
+			"http://www.oasis-open.org/docbook/xml/4.5/docbookx.dtd",
+			None
+		),
+		Ok(EntitySource::Bytes(b"docbook".to_vec()))
+	);
+	assert_eq!(
+		resolver.resolve(
+			Some(XHTML),
+			"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd",
+			None
+		),
+		Ok(EntitySource::Bytes(b"xhtml".to_vec()))
+	);
+	assert_eq!(
+		resolver.resolve(
+			None,
+			"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd",
+			None
+		),
+		Err(Refusal::Denied)
+	);
+}
+
+#[test]
+fn public_id_normalization() {
+	let dir = files(
+		"public_id_normalization",
+		&[(
+			"catalog.xml",
+			&catalog(&format!(
+				"<public publicId='  -//OASIS//DTD&#9;DocBook\n XML V4.5//EN ' uri='docbook/docbookx.dtd'/>
+				<public publicId='{}' uri='xhtml/xhtml1-strict.dtd'/>",
+				XHTML
+			)),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	let docbook = Some(uri(&dir, "docbook/docbookx.dtd"));
+	assert_eq!(
+		resolver.resolve_external_id(Some(" -//OASIS//DTD  DocBook XML\tV4.5//EN"), None),
+		docbook
+	);
+	assert_eq!(
+		resolver.resolve_external_id(Some("urn:publicid:-:OASIS:DTD+DocBook+XML+V4.5:EN"), None),
+		docbook
+	);
+	assert_eq!(
+		resolver.resolve_external_id(None, Some("urn:publicid:-:W3C:DTD+XHTML+1.0+Strict:EN")),
+		Some(uri(&dir, "xhtml/xhtml1-strict.dtd"))
+	);
+	assert_eq!(
+		resolver.resolve_uri("URN:PUBLICID:-:W3C:DTD+XHTML+1.0+Strict:EN"),
+		Some(uri(&dir, "xhtml/xhtml1-strict.dtd"))
+	);
+}
+
+#[test]
+fn rewrite_system() {
+	let dir = files(
+		"rewrite_system",
+		&[(
+			"catalog.xml",
+			&catalog(
+				"<rewriteSystem systemIdStartString='http://www.w3.org/' rewritePrefix='w3/'/>
+				<rewriteSystem systemIdStartString='http://www.w3.org/Graphics/SVG/' rewritePrefix='svg/'/>
+				<rewriteSystem systemIdStartString='http://www.w3.org/Graph' rewritePrefix='graph/'/>",
+			),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	assert_eq!(
+		resolver.resolve_external_id(
+			Some("-//W3C//DTD SVG 1.1//EN"),
+			Some("http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd")
+		),
+		Some(uri(&dir, "svg/1.1/DTD/svg11.dtd"))
+	);
+	assert_eq!(
+		resolver.resolve_external_id(
+			None,
+			Some("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd")
+		),
+		Some(uri(&dir, "w3/TR/xhtml1/DTD/xhtml1-strict.dtd"))
+	);
+}
+
+#[test]
+fn rewrite_system_traversal() {
+	let dir = files(
+		"rewrite_system_traversal",
+		&[
+			(
+				"catalog.xml",
+				&catalog(
+					"<rewriteSystem systemIdStartString='http://www.w3.org/' rewritePrefix='w3/'/>",
+				),
+			),
+			("secret.txt", "secret"),
+		],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	for system_id in [
+		"http://www.w3.org/../secret.txt",
+		"http://www.w3.org/%2e%2e/secret.txt",
+		"http://www.w3.org/TR/%2E%2E/%2e%2E/secret.txt",
+		"http://www.w3.org/..%5Csecret.txt",
+		"http://www.w3.org//etc/passwd",
+		"http://www.w3.org/file:///etc/passwd",
+	] {
+		assert_eq!(resolver.resolve_external_id(None, Some(system_id)), None);
+		assert!(matches!(
+			resolver.resolve(None, system_id, None),
+			Err(Refusal::Denied)
+		));
+	}
+}
+
+#[test]
+fn uri_entries() {
+	let dir = files(
+		"uri",
+		&[(
+			"catalog.xml",
+			&catalog(
+				"<uri name='http://docbook.sourceforge.net/release/xsl/current/html/docbook.xsl' uri='xsl/html/docbook.xsl'/>",
+			),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	assert_eq!(
+		resolver.resolve_uri("http://docbook.sourceforge.net/release/xsl/current/html/docbook.xsl"),
+		Some(uri(&dir, "xsl/html/docbook.xsl"))
+	);
+	assert_eq!(
+		resolver.resolve_external_id(
+			None,
+			Some("http://docbook.sourceforge.net/release/xsl/current/html/docbook.xsl")
+		),
+		None
+	);
+}
+
+#[test]
+fn prefer() {
+	let dir = files(
+		"prefer",
+		&[(
+			"catalog.xml",
+			&catalog(&format!(
+				"<group prefer='system'><public publicId='{}' uri='docbook/docbookx.dtd'/></group>
+				<public publicId='{}' uri='xhtml/xhtml1-strict.dtd'/>",
+				DOCBOOK, XHTML
+			)),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	assert_eq!(
+		resolver.resolve_external_id(Some(DOCBOOK), Some("docbookx.dtd")),
+		None
+	);
+	assert_eq!(
+		resolver.resolve_external_id(Some(DOCBOOK), None),
+		Some(uri(&dir, "docbook/docbookx.dtd"))
+	);
+	assert_eq!(
+		resolver.resolve_external_id(Some(XHTML), Some("xhtml1-strict.dtd")),
+		Some(uri(&dir, "xhtml/xhtml1-strict.dtd"))
+	);
+
+	let mut resolver = CatalogResolver::new().with_prefer(Prefer::System);
+	resolver.add_catalog(dir.join("catalog.xml")).unwrap();
+	assert_eq!(
+		resolver.resolve_external_id(Some(XHTML), Some("xhtml1-strict.dtd")),
+		None
+	);
+}
+
+#[test]
+fn delegate_public() {
+	let dir = files(
+		"delegate_public",
+		&[
+			(
+				"catalog.xml",
+				&catalog(
+					"<delegatePublic publicIdStartString='-//OASIS//' catalog='oasis.xml'/>
+					<delegatePublic publicIdStartString='-//OASIS//DTD DocBook' catalog='docbook/catalog.xml'/>
+					<nextCatalog catalog='next.xml'/>",
+				),
+			),
+			(
+				"docbook/catalog.xml",
+				&catalog("<system systemId='docbookx.dtd' uri='docbookx.dtd'/>"),
+			),
+			(
+				"oasis.xml",
+				&catalog(&format!(
+					"<public publicId='{}' uri='docbook/docbookx.dtd'/>",
+					DOCBOOK
+				)),
+			),
+			(
+				"next.xml",
+				&catalog("<public publicId='-//OASIS//DTD Other//EN' uri='other.dtd'/>"),
+			),
+		],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	// The system ID isn't looked up in delegated catalogs.
+	assert_eq!(
+		resolver.resolve_external_id(Some(DOCBOOK), Some("docbookx.dtd")),
+		Some(uri(&dir, "docbook/docbookx.dtd"))
+	);
+	// Nothing else is consulted after delegation.
+	assert_eq!(
+		resolver.resolve_external_id(Some("-//OASIS//DTD Other//EN"), None),
+		None
+	);
+}
+
+#[test]
+fn next_catalog() {
+	let dir = files(
+		"next_catalog",
+		&[
+			(
+				"catalog.xml",
+				&catalog(
+					"<nextCatalog catalog='missing.xml'/>
+					<nextCatalog catalog='sub/catalog.xml'/>
+					<nextCatalog catalog='catalog.xml'/>",
+				),
+			),
+			(
+				"sub/catalog.xml",
+				&catalog(
+					"<group xml:base='../xhtml/'><system systemId='xhtml1-strict.dtd' uri='xhtml1-strict.dtd'/></group>
+					<nextCatalog catalog='../catalog.xml'/>",
+				),
+			),
+		],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	assert_eq!(
+		resolver.resolve(None, "xhtml1-strict.dtd", Some("doc.xml")),
+		Ok(EntitySource::Bytes(b"xhtml".to_vec()))
+	);
+	assert_eq!(resolver.resolve_external_id(None, Some("svg11.dtd")), None);
+}
+
+#[test]
+fn foreign_elements() {
+	let dir = files(
+		"foreign_elements",
+		&[(
+			"catalog.xml",
+			&catalog(&format!(
+				"<x:public xmlns:x='urn:example' publicId='{0}' uri='a.dtd'/>
+				<x:group xmlns:x='urn:example'><public publicId='{0}' uri='b.dtd'/></x:group>
+				<c:public xmlns:c='urn:oasis:names:tc:entity:xmlns:xml:catalog' publicId='{0}' uri='c.dtd'/>",
+				DOCBOOK
+			)),
+		)],
+	);
+	assert_eq!(
+		resolver(&dir, "catalog.xml").resolve_external_id(Some(DOCBOOK), None),
+		Some(uri(&dir, "c.dtd"))
+	);
+}
+
+#[test]
+fn non_file_uri() {
+	let dir = files(
+		"non_file_uri",
+		&[(
+			"catalog.xml",
+			&catalog(
+				"<system systemId='a.dtd' uri='http://example.com/a.dtd'/>
+				<system systemId='b.dtd' uri='file://example.com/b.dtd'/>",
+			),
+		)],
+	);
+	let mut resolver = resolver(&dir, "catalog.xml");
+	for system_id in ["a.dtd", "b.dtd"] {
+		assert_eq!(
+			resolver.resolve(None, system_id, None),
+			Err(Refusal::Denied)
+		);
+	}
+}
+
+#[test]
+fn malformed_catalog() {
+	let dir = files(
+		"malformed_catalog",
+		&[
+			("malformed.xml", "<catalog></group>"),
+			("truncated.xml", "<catalog><public"),
+		],
+	);
+	assert!(matches!(
+		CatalogResolver::new().add_catalog(dir.join("malformed.xml")),
+		Err(CatalogError::Malformed(_))
+	));
+	assert_eq!(
+		CatalogResolver::new().add_catalog(dir.join("truncated.xml")),
+		Err(CatalogError::Truncated)
+	);
+	assert_eq!(
+		CatalogResolver::new().add_catalog(dir.join("missing.xml")),
+		Err(CatalogError::Unreadable(Refusal::Io(
+			std::io::ErrorKind::NotFound
+		)))
+	);
+}
+
+fn resolver(dir: &Path, catalog: &str) -> CatalogResolver {
+	let mut resolver = CatalogResolver::new();
+	resolver.add_catalog(dir.join(catalog)).unwrap();
+	resolver
+}
+
+fn catalog(entries: &str) -> String {
+	format!(
+		"<?xml version='1.0'?>
+<!DOCTYPE catalog PUBLIC '-//OASIS//DTD XML Catalogs V1.1//EN' 'http://www.oasis-open.org/committees/entity/release/1.1/catalog.dtd'>
+<catalog xmlns='urn:oasis:names:tc:entity:xmlns:xml:catalog'>
+	{}
+</catalog>
+",
+		entries
+	)
+}
+
+/// The `file:` URI of `path` in `dir`.
+fn uri(dir: &Path, path: &str) -> Box<str> {
+	format!("file://{}/{}", dir.canonicalize().unwrap().display(), path).into()
+}
+
+/// Writes `catalogs` and some vendored DTDs into a fresh directory for `test`.
+fn files(test: &str, catalogs: &[(&str, &str)]) -> PathBuf {
+	let dir = std::env::temp_dir()
+		.join(format!("yolo-xml-catalog-{}", std::process::id()))
+		.join(test);
+	let _ = fs::remove_dir_all(&dir);
+	for (path, content) in [
+		("docbook/docbookx.dtd", "docbook"),
+		("xhtml/xhtml1-strict.dtd", "\u{FEFF}xhtml"),
+	]
+	.iter()
+	.chain(catalogs)
+	{
+		let path = dir.join(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, content).unwrap();
+	}
+	dir
+}
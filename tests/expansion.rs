@@ -0,0 +1,398 @@
+use std::{collections::HashMap, mem::MaybeUninit, str::from_utf8};
+use yolo_xml::{
+	buffer::StrBuf,
+	expansion::{Expander, ExpansionError, Suspended},
+	resolver::{DenyAll, EntityResolver, EntitySource, Refusal},
+	scanner::{Error, Event, Scanner, ScannerError},
+	well_formedness::{Checker, WellFormednessError},
+};
+
+#[test]
+fn expansion() {
+	expect_content(
+		expander(),
+		&[
+			"<!DOCTYPE a [<!ENTITY e '<b>&f;</b>'><!ENTITY f 'x&#38;#38;y'>]>",
+			"<a>&e;&lt;</a>",
+		],
+		"<a>&e;<b>&f;x&#38;y</b>&lt;&#60;</a>",
+		None,
+	);
+}
+
+#[test]
+fn expansion_att_value() {
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY q '\"&#39;'>]><a b=\"&q;\"/>"],
+		"<a b=\"&q;&#34;&#39;\"/>",
+		None,
+	);
+}
+
+#[test]
+fn expansion_continued_by_input() {
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY e 'x]'>]><a>&e;", "y</a>"],
+		"<a>&e;x]y</a>",
+		None,
+	);
+}
+
+#[test]
+fn external_entity() {
+	let mut resolver = Entities(HashMap::new());
+	resolver.0.insert("ents/e.xml", "<b/>x]");
+	expect_content_resolved(
+		expander().with_base_uri("doc.xml"),
+		Some(&mut resolver),
+		&["<!DOCTYPE a [<!ENTITY e PUBLIC '-//E//EN' 'e.xml'>]><a>&e;y</a>"],
+		"<a>&e;<b/>x]y</a>",
+		None,
+	);
+}
+
+#[test]
+fn external_entity_text_declaration() {
+	let mut resolver = Entities(HashMap::new());
+	resolver
+		.0
+		.insert("ents/e.xml", "<?xml encoding='UTF-8'?><b/>x");
+	resolver
+		.0
+		.insert("ents/f.xml", "<?xml version='1.0' encoding='UTF-8'?>");
+	expect_content_resolved(
+		expander().with_base_uri("doc.xml"),
+		Some(&mut resolver),
+		&["<!DOCTYPE a [<!ENTITY e SYSTEM 'e.xml'><!ENTITY f SYSTEM 'f.xml'>]><a>&e;&f;y</a>"],
+		"<a>&e;<b/>x&f;y</a>",
+		None,
+	);
+}
+
+#[test]
+fn external_entity_text_declaration_invalid() {
+	let mut resolver = Entities(HashMap::new());
+	resolver.0.insert("ents/e.xml", "<?xml version='1.0'?>x");
+	expect_content_resolved(
+		expander().with_base_uri("doc.xml"),
+		Some(&mut resolver),
+		&["<!DOCTYPE a [<!ENTITY e SYSTEM 'e.xml'>]><a>&e;</a>"],
+		"<a>&e;",
+		Some(ExpansionError::WellFormednessError(
+			WellFormednessError::ScannerError(ScannerError::XmlError(
+				Error::Expected80EncodingDecl,
+			)),
+		)),
+	);
+}
+
+#[test]
+fn external_entity_counts_as_input() {
+	let mut resolver = Entities(HashMap::new());
+	resolver.0.insert(
+		"ents/e.xml",
+		"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+	);
+	expect_content_resolved(
+		expander()
+			.with_base_uri("doc.xml")
+			.with_amplification_limit(1, 0),
+		Some(&mut resolver),
+		&["<!DOCTYPE a [<!ENTITY e SYSTEM 'e.xml'>]><a>&e;</a>"],
+		"<a>&e;0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef</a>",
+		None,
+	);
+}
+
+#[test]
+fn external_entity_skipped() {
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY e SYSTEM 'e.xml'>]><a>&e;</a>"],
+		"<a>&e;</a>",
+		None,
+	);
+}
+
+#[test]
+fn external_entity_refused() {
+	expect_content_resolved(
+		expander(),
+		Some(&mut DenyAll),
+		&["<!DOCTYPE a [<!ENTITY e SYSTEM 'e.xml'>]><a>&e;</a>"],
+		"<a>&e;",
+		Some(ExpansionError::Refused {
+			name: "e".into(),
+			refusal: Refusal::Denied,
+		}),
+	);
+}
+
+#[test]
+fn expansion_line_breaks() {
+	// Character references aren't recognized in CDATA sections, comments and processing instructions.
+	// (The whitespace after a processing instruction's target isn't reported.)
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY e '<![CDATA[x&#13;y]]><!--x&#13;y--><?p x&#13;y?>x&#13;&#133;&#8232;y'>]><a>&e;</a>"],
+		"<a>&e;<![CDATA[x\ry]]><!--x\ry--><?px\ry?>x\r\u{85}\u{2028}y</a>",
+		None,
+	);
+}
+
+#[test]
+fn expansion_att_value_whitespace() {
+	let input = "<!DOCTYPE a [<!ENTITY e 'x&#13;&#10;&#9; &#34;y'>]><a b='&e;\t'/>";
+	expect_content(
+		expander(),
+		&[input],
+		"<a b='&e;x&#13;&#10;&#9; &#34;y\t'/>",
+		None,
+	);
+	expect_content(
+		Expander::new(
+			Checker::new(
+				Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+				NAME_BUDGET,
+			),
+			REPLACEMENT_TEXT_BUDGET,
+		),
+		&[input],
+		"<a b='&e;x&#13;&#10;&#9; &#34;y '/>",
+		None,
+	);
+}
+
+#[test]
+fn recursive_entity_reference() {
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY x '&#38;y;'><!ENTITY y '&x;'>]><a>&x;</a>"],
+		"<a>&x",
+		Some(ExpansionError::WellFormednessError(
+			WellFormednessError::RecursiveEntityReference("x".into()),
+		)),
+	);
+}
+
+#[test]
+fn unbalanced_entity() {
+	expect_content(
+		expander(),
+		&["<!DOCTYPE a [<!ENTITY e '<b>'>]><a>&e;</b></a>"],
+		"<a>&e;<b>",
+		Some(ExpansionError::UnbalancedEntity("e".into())),
+	);
+}
+
+#[test]
+fn depth_limit() {
+	expect_content(
+		expander().with_depth_limit(2),
+		&["<!DOCTYPE a [<!ENTITY x '&y;'><!ENTITY y '&z;'><!ENTITY z 'z'>]><a>&x;</a>"],
+		"<a>&x;&y;&z",
+		Some(ExpansionError::DepthLimitExceeded),
+	);
+}
+
+#[test]
+fn expanded_bytes_limit() {
+	expect_content(
+		expander().with_expanded_bytes_limit(5),
+		&["<a>&lt;&gt;</a>"],
+		"<a>&lt;&#60;&gt",
+		Some(ExpansionError::ExpandedBytesLimitExceeded),
+	);
+}
+
+#[test]
+fn billion_laughs() {
+	let mut input = "<!DOCTYPE a [<!ENTITY lol0 'lol'>".to_owned();
+	for i in 1..10 {
+		input += &format!(
+			"<!ENTITY lol{} '{}'>",
+			i,
+			format!("&lol{};", i - 1).repeat(10)
+		);
+	}
+	input += "]><a>&lol9;</a>";
+	expect_error(
+		Expander::new(Checker::new(Scanner::new(DEPTH_LIMIT), 1024), 1024),
+		&input,
+		ExpansionError::AmplificationLimitExceeded,
+	);
+}
+
+#[test]
+fn quadratic_blowup() {
+	let input = format!(
+		"<!DOCTYPE a [<!ENTITY a '{}'>]><a>{}</a>",
+		"a".repeat(1000),
+		"&a;".repeat(2000),
+	);
+	expect_error(
+		expander(),
+		&input,
+		ExpansionError::AmplificationLimitExceeded,
+	);
+}
+
+const DEPTH_LIMIT: usize = 16;
+const NAME_BUDGET: usize = 64;
+const REPLACEMENT_TEXT_BUDGET: usize = 1024;
+
+fn expander() -> Expander {
+	Expander::new(
+		Checker::new(Scanner::new(DEPTH_LIMIT), NAME_BUDGET),
+		REPLACEMENT_TEXT_BUDGET,
+	)
+}
+
+/// Resolves entities from the system IDs in `ents/` (for a base URI of `doc.xml`).
+struct Entities(HashMap<&'static str, &'static str>);
+
+impl EntityResolver for Entities {
+	fn resolve(
+		&mut self,
+		_public_id: Option<&str>,
+		system_id: &str,
+		base_uri: Option<&str>,
+	) -> Result<EntitySource, Refusal> {
+		assert_eq!(base_uri, Some("doc.xml"));
+		self.0
+			.get(format!("ents/{}", system_id).as_str())
+			.map(|text| EntitySource::Text(text.to_string()))
+			.ok_or(Refusal::Io(std::io::ErrorKind::NotFound))
+	}
+}
+
+/// Like [`expect_content_resolved`], but skips external entities.
+fn expect_content(
+	expander: Expander,
+	input: &[&str],
+	content: &str,
+	error: Option<ExpansionError>,
+) {
+	expect_content_resolved(expander, None, input, content, error)
+}
+
+/// Makes `input` available one piece at a time, and checks the events after the document type declaration,
+/// written back as text, with a tiny expansion buffer.
+///
+/// External entities are resolved with `resolver`, if any.
+fn expect_content_resolved(
+	mut expander: Expander,
+	mut resolver: Option<&mut dyn EntityResolver>,
+	input: &[&str],
+	content: &str,
+	error: Option<ExpansionError>,
+) {
+	let mut buffer = vec![MaybeUninit::uninit(); input.iter().map(|piece| piece.len()).sum()];
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	// Just large enough for `<![CDATA[`.
+	let mut expansion_buffer = [MaybeUninit::uninit(); 9];
+	let mut expansion_buffer = StrBuf::new(&mut expansion_buffer);
+	let mut input = input.iter();
+
+	let mut written = String::new();
+	let mut in_doctype = false;
+	let mut stalled = false;
+	let outcome = loop {
+		match expander.resume(&mut buffer, &mut expansion_buffer) {
+			Ok(Ok(Some(event))) => {
+				stalled = false;
+				match event {
+					Event::DoctypedeclStart(_) => in_doctype = true,
+					Event::DoctypedeclEnd(_) => in_doctype = false,
+					_ if in_doctype => (),
+					event => written += &write(&event),
+				}
+			}
+			Ok(Ok(None)) => break None,
+			Ok(Err(error)) => break Some(error),
+			Err(Suspended::ResolveEntity) => {
+				if let Some(resolver) = &mut resolver {
+					if let Err(error) = expander.resolve_with(*resolver) {
+						break Some(error);
+					}
+				}
+			}
+			// Space in the expansion buffer may be all that's missing.
+			Err(Suspended::MoreInputRequired) => unsafe {
+				buffer.unshift_reset();
+				expansion_buffer.unshift_reset();
+				match input.next() {
+					Some(piece) => {
+						for (slot, byte) in buffer
+							.remaining_maybe_uninitialized()
+							.iter_mut()
+							.zip(piece.as_bytes())
+						{
+							slot.write(*byte);
+						}
+						buffer.assume_filled_n_remaining(piece.len());
+					}
+					None if stalled => break None,
+					None => stalled = true,
+				}
+			},
+		}
+	};
+	assert_eq!(written, content);
+	assert_eq!(outcome, error);
+}
+
+/// Scans `input` at once and expects `error` eventually.
+fn expect_error(mut expander: Expander, input: &str, error: ExpansionError) {
+	let mut buffer = input.bytes().map(MaybeUninit::new).collect::<Vec<_>>();
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	unsafe { buffer.assume_filled_n_remaining(input.len()) };
+	let mut expansion_buffer = [MaybeUninit::uninit(); 64];
+	let mut expansion_buffer = StrBuf::new(&mut expansion_buffer);
+
+	loop {
+		match expander.resume(&mut buffer, &mut expansion_buffer) {
+			Ok(Ok(Some(_))) => (),
+			Ok(Ok(None)) => panic!("Unexpected end of document."),
+			Ok(Err(actual)) => break assert_eq!(actual, error),
+			Err(_) => unsafe {
+				assert!(!buffer.filled().is_empty(), "Ran out of input.");
+				buffer.unshift_reset();
+				expansion_buffer.unshift_reset();
+			},
+		}
+	}
+}
+
+/// The text `event` was scanned from.
+fn write(event: &Event) -> String {
+	match event {
+		Event::StartTagStart(b)
+		| Event::StartTagEnd(b)
+		| Event::EndTagEnd(b)
+		| Event::EntityRefStart(b)
+		| Event::EntityRefEnd(b)
+		| Event::CharRefEnd(b, _)
+		| Event::AttValueStart(b)
+		| Event::AttValueEnd(b) => from_utf8(&b[..]).unwrap().to_owned(),
+		Event::StartTagEndEmpty(b) | Event::EndTagStart(b) | Event::CharRefDecimalStart(b) => {
+			from_utf8(&b[..]).unwrap().to_owned()
+		}
+		Event::CharRefDecimalChunk(b) => from_utf8(b).unwrap().to_owned(),
+		Event::CDStart(b) => from_utf8(&b[..]).unwrap().to_owned(),
+		Event::CommentStart(b) => from_utf8(&b[..]).unwrap().to_owned(),
+		Event::CDEnd(b) | Event::CommentEnd(b) => from_utf8(&b[..]).unwrap().to_owned(),
+		Event::PIStart(b) | Event::PIEnd(b) => from_utf8(&b[..]).unwrap().to_owned(),
+		Event::NameChunk(s)
+		| Event::CharDataChunk(s)
+		| Event::AttValueChunk(s)
+		| Event::CDataChunk(s)
+		| Event::CommentChunk(s)
+		| Event::PIChunk(s) => s.to_string(),
+		// The scanner doesn't report the whitespace and `=` around attribute names.
+		Event::AttributeNameChunk(s) => format!(" {}=", s),
+		event => panic!("Unexpected event: {:?}", event),
+	}
+}
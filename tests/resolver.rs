@@ -0,0 +1,127 @@
+use std::{fs, io::ErrorKind, path::PathBuf};
+use yolo_xml::resolver::{EntityResolver, EntitySource, FileSystemResolver, Refusal};
+
+#[test]
+fn resolve_relative() {
+	let root = files("resolve_relative");
+	assert_eq!(
+		resolver(&root).resolve(None, "b.ent", Some("sub/doc.xml")),
+		Ok(EntitySource::Bytes(b"b".to_vec()))
+	);
+	assert_eq!(
+		resolver(&root).resolve(Some("-//A//EN"), "./a.ent", None),
+		Ok(EntitySource::Bytes(b"a".to_vec()))
+	);
+}
+
+#[test]
+fn refuse_parent_segments() {
+	let root = files("refuse_parent_segments");
+	assert_eq!(
+		resolver(&root).resolve(None, "../secret", None),
+		Err(Refusal::OutOfBounds)
+	);
+	assert_eq!(
+		resolver(&root).resolve(None, "a.ent", Some("sub/../../doc.xml")),
+		Err(Refusal::OutOfBounds)
+	);
+}
+
+#[test]
+fn refuse_absolute() {
+	let root = files("refuse_absolute");
+	let secret = root.join("../secret");
+	for system_id in [
+		secret.to_str().unwrap(),
+		"file:///etc/passwd",
+		"http://example.com/a.ent",
+		"sub%2Fb.ent",
+	] {
+		assert_eq!(
+			resolver(&root).resolve(None, system_id, None),
+			Err(Refusal::Denied)
+		);
+	}
+}
+
+#[test]
+#[cfg(unix)]
+fn refuse_escaping_symlink() {
+	let root = files("refuse_escaping_symlink");
+	std::os::unix::fs::symlink("../secret", root.join("link")).unwrap();
+	assert_eq!(
+		resolver(&root).resolve(None, "link", None),
+		Err(Refusal::OutOfBounds)
+	);
+}
+
+#[test]
+#[cfg(unix)]
+fn follow_contained_symlink() {
+	let root = files("follow_contained_symlink");
+	std::os::unix::fs::symlink("sub/b.ent", root.join("link")).unwrap();
+	assert_eq!(
+		resolver(&root).resolve(None, "link", None),
+		Ok(EntitySource::Bytes(b"b".to_vec()))
+	);
+}
+
+#[test]
+fn refuse_directory() {
+	let root = files("refuse_directory");
+	assert_eq!(
+		resolver(&root).resolve(None, "sub", None),
+		Err(Refusal::Denied)
+	);
+}
+
+#[test]
+#[cfg(unix)]
+fn refuse_fifo() {
+	let root = files("refuse_fifo");
+	let fifo = std::ffi::CString::new(root.join("fifo").to_str().unwrap()).unwrap();
+	assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o600) }, 0);
+	// Without a writer, this would block if opened normally.
+	assert_eq!(
+		resolver(&root).resolve(None, "fifo", None),
+		Err(Refusal::Denied)
+	);
+}
+
+#[test]
+fn size_limit() {
+	let root = files("size_limit");
+	assert_eq!(
+		resolver(&root)
+			.with_size_limit(0)
+			.resolve(None, "a.ent", None),
+		Err(Refusal::TooLarge)
+	);
+}
+
+#[test]
+fn not_found() {
+	let root = files("not_found");
+	assert_eq!(
+		resolver(&root).resolve(None, "c.ent", None),
+		Err(Refusal::Io(ErrorKind::NotFound))
+	);
+}
+
+fn resolver(root: &PathBuf) -> FileSystemResolver {
+	FileSystemResolver::new(root).unwrap()
+}
+
+/// Creates `root/a.ent`, `root/sub/b.ent` and `secret` next to `root`, in a fresh directory for `test`.
+fn files(test: &str) -> PathBuf {
+	let dir = std::env::temp_dir()
+		.join(format!("yolo-xml-resolver-{}", std::process::id()))
+		.join(test);
+	let _ = fs::remove_dir_all(&dir);
+	let root = dir.join("root");
+	fs::create_dir_all(root.join("sub")).unwrap();
+	fs::write(root.join("a.ent"), "a").unwrap();
+	fs::write(root.join("sub/b.ent"), "\u{FEFF}b").unwrap();
+	fs::write(dir.join("secret"), "secret").unwrap();
+	root
+}
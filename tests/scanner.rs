@@ -4,7 +4,10 @@ use tracing_subscriber::{prelude::__tracing_subscriber_SubscriberExt, Registry};
 use tracing_tree::HierarchicalLayer;
 use yolo_xml::{
 	buffer::StrBuf,
-	scanner::{Error, Event, Scanner, ScannerError},
+	scanner::{
+		DefaultDecl, Error, Event, Repetition, SDDecl, Scanner, ScannerError, StringType,
+		TokenizedType, VersionPolicy, XmlVersion,
+	},
 };
 
 #[test]
@@ -13,69 +16,2018 @@ fn xml_declaration() {
 
 	expect_events(
 		"<?xml version=\"1.1\"?>",
-		&[Event::VersionChunk(&mut b"1.1".to_owned())],
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn encoding_declaration() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.0' encoding=\"ISO-8859-1\"?><a/>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::EncNameChunk(&mut b"ISO-8859-1".to_owned()),
+			Event::EncNameEnd(&mut b"\"".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn encoding_declaration_split() {
+	setup();
+
+	expect_events_fed(
+		&[
+			"<?xml version='1.1' encoding='UT",
+			"F-16' standalone='no'?>",
+		],
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::EncNameChunk(&mut b"UT".to_owned()),
+			Event::EncNameChunk(&mut b"F-16".to_owned()),
+			Event::EncNameEnd(&mut b"'".to_owned()),
+			Event::SDDecl(SDDecl::No(&mut b"no".to_owned())),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn encoding_declaration_pauses() {
+	setup();
+
+	let mut buffer = Vec::from_iter(
+		"<?xml version='1.1' encoding='UTF-16'?><a/>"
+			.bytes()
+			.map(MaybeUninit::new),
+	);
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	unsafe {
+		buffer.assume_filled_n_remaining(buffer.remaining_len());
+	}
+
+	let mut scanner = Scanner::new(DEPTH_LIMIT);
+	while !matches!(
+		scanner.resume(&mut buffer).unwrap().unwrap().unwrap(),
+		Event::EncNameEnd(_)
+	) {}
+	assert_eq!(buffer.filled(), b"?><a/>");
+}
+
+#[test]
+fn encoding_declaration_invalid_start() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1' encoding='8bit'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected81EncName)),
+	);
+}
+
+#[test]
+fn encoding_declaration_invalid() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1' encoding='UTF 8'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::EncNameChunk(&mut b"UTF".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedLiteral(b"'"))),
+	);
+}
+
+#[test]
+fn standalone_declaration() {
+	setup();
+
+	expect_events(
+		"<?xml version=\"1.1\" standalone = 'yes' ?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::SDDecl(SDDecl::Yes(&mut b"yes".to_owned())),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn standalone_declaration_1_0() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.0' standalone=\"no\"?><a/>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::SDDecl(SDDecl::No(&mut b"no".to_owned())),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn standalone_declaration_invalid() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1' standalone='Yes'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedYesOrNo)),
+	);
+}
+
+#[test]
+fn standalone_declaration_mismatched_quotes() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1' standalone='no\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::SDDecl(SDDecl::No(&mut b"no".to_owned())),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedLiteral(b"'"))),
+	);
+}
+
+#[test]
+fn downgrade_1() {
+	setup();
+
+	expect_events(" ", &[], None);
+}
+
+#[test]
+fn downgrade_2() {
+	setup();
+
+	expect_events(
+		"<?xml version=\"1.0\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn downgrade_3() {
+	setup();
+
+	expect_events(
+		"<?xml version=\"1.12345\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"12345".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn downgrade_4() {
+	setup();
+
+	expect_events(
+		"<?xml version=\"1.77777\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"77777".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn version() {
+	setup();
+
+	assert_eq!(version_after_events("<?xml version='1.1'?>", 1), None);
+	assert_eq!(
+		version_after_events("<?xml version='1.1'?>", 2),
+		Some(XmlVersion::Xml1_1)
+	);
+	assert_eq!(
+		version_after_events("<?xml version='1.0'?>", 2),
+		Some(XmlVersion::Xml1_0)
+	);
+	assert_eq!(
+		version_after_events("<?xml version='1.7'?>", 2),
+		Some(XmlVersion::Xml1_0)
+	);
+	assert_eq!(version_after_events("<a/>", 1), Some(XmlVersion::Xml1_0));
+}
+
+#[test]
+fn version_split() {
+	setup();
+
+	expect_events_fed(
+		&["<?xml version='", "1.1'?>"],
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn version_policy_xml1_0() {
+	setup();
+
+	let scanner = || Scanner::new(DEPTH_LIMIT).with_version_policy(VersionPolicy::Xml1_0);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.0'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+	expect_scanner_events(
+		scanner(),
+		"<a/>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.1'?>",
+		&[Event::XmlDeclStart(&mut b"<?xml".to_owned())],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Xml1_0,
+		))),
+	);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.2'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"2".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Xml1_0,
+		))),
+	);
+}
+
+#[test]
+fn version_policy_xml1_1() {
+	setup();
+
+	let scanner = || Scanner::new(DEPTH_LIMIT).with_version_policy(VersionPolicy::Xml1_1);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version=\"1.1\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+	expect_scanner_events(
+		scanner(),
+		"<a/>",
+		&[],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Xml1_1,
+		))),
+	);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version=\"1.0\"?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Xml1_1,
+		))),
+	);
+}
+
+#[test]
+fn version_policy_both() {
+	setup();
+
+	let scanner = || Scanner::new(DEPTH_LIMIT).with_version_policy(VersionPolicy::Both);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.0'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.1'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+		],
+		None,
+	);
+	expect_scanner_events(
+		scanner(),
+		"<?xml version='1.10'?>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"10".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Both,
+		))),
+	);
+	expect_scanner_events_fed(
+		scanner(),
+		&["<?xml version='1.0", "0'?>"],
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::VersionNotAllowed(
+			VersionPolicy::Both,
+		))),
+	);
+}
+
+#[test]
+fn downgrade_depth() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.0'?><a><b><c><d><e><f/></e></d></c></b></a>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "d".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "f".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "d".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_declaration_empty_any() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a EMPTY><!ELEMENT b ANY >]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EMPTY(&mut b"EMPTY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::ANY(&mut b"ANY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_declaration_mixed() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a (#PCDATA)><!ELEMENT b ( #PCDATA | a |b )*>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::PCDATA(&mut b"#PCDATA".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::PCDATA(&mut b"#PCDATA".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::Repetition(Repetition::ZeroOrMore(&mut b"*".to_owned())),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_declaration_mixed_missing_repetition() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a (#PCDATA|b)>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::PCDATA(&mut b"#PCDATA".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedLiteral(b"*"))),
+	);
+}
+
+#[test]
+fn element_declaration_children() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a (b, (c | d)*, e?)+><!ELEMENT b (c)>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::SeqSeparator(&mut b",".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NameChunk(&mut "d".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::Repetition(Repetition::ZeroOrMore(&mut b"*".to_owned())),
+			Event::SeqSeparator(&mut b",".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::Repetition(Repetition::ZeroOrOne(&mut b"?".to_owned())),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::Repetition(Repetition::OneOrMore(&mut b"+".to_owned())),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_declaration_mixed_separators() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a (b,c|d)>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::SeqSeparator(&mut b",".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::MixedChoiceAndSeq)),
+	);
+}
+
+#[test]
+fn element_declaration_missing_content_spec() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a >]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected46ContentSpec)),
+	);
+}
+
+#[test]
+fn attribute_list_declaration() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ATTLIST a b CDATA #REQUIRED c (x|1 ) 'x' d NOTATION ( n ) #IMPLIED e NMTOKEN #FIXED \"v\" >]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::StringType(StringType::CDATA(&mut b"CDATA".to_owned())),
+			Event::DefaultDecl(DefaultDecl::REQUIRED(&mut b"#REQUIRED".to_owned())),
+			Event::AttributeNameChunk(&mut "c".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NmtokenChunk(&mut "x".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NmtokenChunk(&mut "1".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "x".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "d".to_owned()),
+			Event::NOTATION(&mut b"NOTATION".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NameChunk(&mut "n".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttributeNameChunk(&mut "e".to_owned()),
+			Event::TokenizedType(TokenizedType::NMTOKEN(&mut b"NMTOKEN".to_owned())),
+			Event::DefaultDecl(DefaultDecl::FIXED(&mut b"#FIXED".to_owned())),
+			Event::AttValueStart(&mut b"\"".to_owned()),
+			Event::AttValueChunk(&mut "v".to_owned()),
+			Event::AttValueEnd(&mut b"\"".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_list_declaration_empty() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ATTLIST a><!ATTLIST b >]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_list_declaration_missing_default() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ATTLIST a b CDATA>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::StringType(StringType::CDATA(&mut b"CDATA".to_owned())),
+		],
+		Some(ScannerError::XmlError(Error::Expected3Whitespace)),
+	);
+}
+
+#[test]
+fn attribute_list_declaration_invalid_type() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ATTLIST a b STRING #IMPLIED>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected54AttType)),
+	);
+}
+
+#[test]
+fn attribute_list_declaration_invalid_enumeration() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ATTLIST a b (x,y) #IMPLIED>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::NmtokenChunk(&mut "x".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedGroupSeparatorOrEnd)),
+	);
+}
+
+#[test]
+fn attribute_value_normalization() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+		"<a b=' x\ty\r\n z '/>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut " x y  z ".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_value_normalization_tokenized() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+		"<!DOCTYPE a[<!ATTLIST a b NMTOKENS \"\tx  y \" c CDATA #IMPLIED><!ATTLIST a c ID #IMPLIED>]><a b=' x\r\n&e;  y ' c=' z '/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::TokenizedType(TokenizedType::NMTOKENS(&mut b"NMTOKENS".to_owned())),
+			Event::AttValueStart(&mut b"\"".to_owned()),
+			Event::AttValueChunk(&mut "x".to_owned()),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::AttValueChunk(&mut "y".to_owned()),
+			Event::AttValueEnd(&mut b"\"".to_owned()),
+			Event::AttributeNameChunk(&mut "c".to_owned()),
+			Event::StringType(StringType::CDATA(&mut b"CDATA".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "c".to_owned()),
+			Event::TokenizedType(TokenizedType::ID(&mut b"ID".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "x".to_owned()),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::EntityRefStart(&mut b"&".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityRefEnd(&mut b";".to_owned()),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::AttValueChunk(&mut "y".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "c".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut " z ".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_value_normalization_tokenized_split() {
+	setup();
+
+	expect_scanner_events_fed(
+		Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+		&[
+			"<!DOCTYPE a[<!ATTLIST a b IDREFS #IMPLIED>]><a b='x  ",
+			"  y  ",
+			"  '/>",
+		],
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::TokenizedType(TokenizedType::IDREFS(&mut b"IDREFS".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "x".to_owned()),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::AttValueChunk(&mut "y".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_value_normalization_tokenized_char_ref() {
+	setup();
+
+	// Spaces from character references are left to the caller.
+	expect_scanner_events(
+		Scanner::new(DEPTH_LIMIT).with_literal_attribute_value_normalization(true),
+		"<!DOCTYPE a[<!ATTLIST a b NMTOKENS #IMPLIED>]><a b='&#32; x &#32;'/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::TokenizedType(TokenizedType::NMTOKENS(&mut b"NMTOKENS".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"32".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), ' '),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::AttValueChunk(&mut "x".to_owned()),
+			Event::AttValueChunk(&mut " ".to_owned()),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"32".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), ' '),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attribute_value_normalization_budget() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new(DEPTH_LIMIT)
+			.with_literal_attribute_value_normalization(true)
+			.with_attribute_type_budget(4),
+		"<!DOCTYPE a[<!ATTLIST a b ID #IMPLIED c ID #IMPLIED><!ATTLIST a bc ID #IMPLIED>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::TokenizedType(TokenizedType::ID(&mut b"ID".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttributeNameChunk(&mut "c".to_owned()),
+			Event::TokenizedType(TokenizedType::ID(&mut b"ID".to_owned())),
+			Event::DefaultDecl(DefaultDecl::IMPLIED(&mut b"#IMPLIED".to_owned())),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::AttributeTypeBudgetExceeded),
+	);
+}
+
+#[test]
+fn entity_declaration() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ENTITY % p 'x'><!ENTITY e \"<b>%p;&#38;&e;</b>\" >]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::PEDeclMarker(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::EntityValueStart(&mut b"'".to_owned()),
+			Event::EntityValueChunk(&mut "x".to_owned()),
+			Event::EntityValueEnd(&mut b"'".to_owned()),
+			Event::EntityDeclEnd(&mut b">".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityValueStart(&mut b"\"".to_owned()),
+			Event::EntityValueChunk(&mut "<b>".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"38".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), '&'),
+			Event::EntityRefStart(&mut b"&".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityRefEnd(&mut b";".to_owned()),
+			Event::EntityValueChunk(&mut "</b>".to_owned()),
+			Event::EntityValueEnd(&mut b"\"".to_owned()),
+			Event::EntityDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn entity_declaration_empty_value() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ENTITY e ''>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityValueStart(&mut b"'".to_owned()),
+			Event::EntityValueEnd(&mut b"'".to_owned()),
+			Event::EntityDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn entity_declaration_unterminated_reference() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ENTITY e '%p'>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityValueStart(&mut b"'".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedLiteral(b";"))),
+	);
+}
+
+#[test]
+fn entity_declaration_missing_end() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ENTITY e 'x' e>]>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::NameChunk(&mut "e".to_owned()),
+			Event::EntityValueStart(&mut b"'".to_owned()),
+			Event::EntityValueChunk(&mut "x".to_owned()),
+			Event::EntityValueEnd(&mut b"'".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedEntityDeclEnd)),
+	);
+}
+
+#[test]
+fn doctype_public() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd\"><html/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "html".to_owned()),
+			Event::PUBLIC(&mut b"PUBLIC".to_owned()),
+			Event::PubidLiteralStart(&mut b"\"".to_owned()),
+			Event::PubidLiteralChunk(&mut "-//W3C//DTD XHTML 1.0 Strict//EN".to_owned()),
+			Event::PubidLiteralEnd(&mut b"\"".to_owned()),
+			Event::SystemLiteralStart(&mut b"\"".to_owned()),
+			Event::SystemLiteralChunk(&mut "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd".to_owned()),
+			Event::SystemLiteralEnd(&mut b"\"".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "html".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn doctype_system_external_entities() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a SYSTEM 'a.dtd' [<!ENTITY % p PUBLIC \"p\" ''><!ENTITY i SYSTEM \"i.png\" NDATA png >]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::SYSTEM(&mut b"SYSTEM".to_owned()),
+			Event::SystemLiteralStart(&mut b"'".to_owned()),
+			Event::SystemLiteralChunk(&mut "a.dtd".to_owned()),
+			Event::SystemLiteralEnd(&mut b"'".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::PEDeclMarker(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::PUBLIC(&mut b"PUBLIC".to_owned()),
+			Event::PubidLiteralStart(&mut b"\"".to_owned()),
+			Event::PubidLiteralChunk(&mut "p".to_owned()),
+			Event::PubidLiteralEnd(&mut b"\"".to_owned()),
+			Event::SystemLiteralStart(&mut b"'".to_owned()),
+			Event::SystemLiteralEnd(&mut b"'".to_owned()),
+			Event::EntityDeclEnd(&mut b">".to_owned()),
+			Event::EntityDeclStart(&mut b"<!ENTITY".to_owned()),
+			Event::NameChunk(&mut "i".to_owned()),
+			Event::SYSTEM(&mut b"SYSTEM".to_owned()),
+			Event::SystemLiteralStart(&mut b"\"".to_owned()),
+			Event::SystemLiteralChunk(&mut "i.png".to_owned()),
+			Event::SystemLiteralEnd(&mut b"\"".to_owned()),
+			Event::NDATA(&mut b"NDATA".to_owned()),
+			Event::NameChunk(&mut "png".to_owned()),
+			Event::EntityDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn notation_declaration() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!NOTATION n PUBLIC 'n'><!NOTATION m PUBLIC 'm' \"m.exe\" ><!NOTATION o SYSTEM 'o'>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::NotationDeclStart(&mut b"<!NOTATION".to_owned()),
+			Event::NameChunk(&mut "n".to_owned()),
+			Event::PUBLIC(&mut b"PUBLIC".to_owned()),
+			Event::PubidLiteralStart(&mut b"'".to_owned()),
+			Event::PubidLiteralChunk(&mut "n".to_owned()),
+			Event::PubidLiteralEnd(&mut b"'".to_owned()),
+			Event::NotationDeclEnd(&mut b">".to_owned()),
+			Event::NotationDeclStart(&mut b"<!NOTATION".to_owned()),
+			Event::NameChunk(&mut "m".to_owned()),
+			Event::PUBLIC(&mut b"PUBLIC".to_owned()),
+			Event::PubidLiteralStart(&mut b"'".to_owned()),
+			Event::PubidLiteralChunk(&mut "m".to_owned()),
+			Event::PubidLiteralEnd(&mut b"'".to_owned()),
+			Event::SystemLiteralStart(&mut b"\"".to_owned()),
+			Event::SystemLiteralChunk(&mut "m.exe".to_owned()),
+			Event::SystemLiteralEnd(&mut b"\"".to_owned()),
+			Event::NotationDeclEnd(&mut b">".to_owned()),
+			Event::NotationDeclStart(&mut b"<!NOTATION".to_owned()),
+			Event::NameChunk(&mut "o".to_owned()),
+			Event::SYSTEM(&mut b"SYSTEM".to_owned()),
+			Event::SystemLiteralStart(&mut b"'".to_owned()),
+			Event::SystemLiteralChunk(&mut "o".to_owned()),
+			Event::SystemLiteralEnd(&mut b"'".to_owned()),
+			Event::NotationDeclEnd(&mut b">".to_owned()),
+			Event::DoctypedeclEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn system_literal_fragment() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a SYSTEM 'a.dtd#x'>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::SYSTEM(&mut b"SYSTEM".to_owned()),
+			Event::SystemLiteralStart(&mut b"'".to_owned()),
+			Event::SystemLiteralChunk(&mut "a.dtd".to_owned()),
+		],
+		Some(ScannerError::XmlError(
+			Error::FragmentIdentifierInSystemLiteral,
+		)),
+	);
+}
+
+#[test]
+fn pubid_literal_invalid() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a PUBLIC 'a{' ''>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::PUBLIC(&mut b"PUBLIC".to_owned()),
+			Event::PubidLiteralStart(&mut b"'".to_owned()),
+			Event::PubidLiteralChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected13PubidChar)),
+	);
+}
+
+#[test]
+fn external_subset() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<?xml encoding='UTF-8'?><!ELEMENT a EMPTY><![INCLUDE[ <!ATTLIST a> ]]><![ IGNORE [<!ELEMENT a ANY><![x]]>]]]>%p;",
+		&[
+			Event::TextDeclStart(&mut b"<?xml".to_owned()),
+			Event::EncNameChunk(&mut b"UTF-8".to_owned()),
+			Event::EncNameEnd(&mut b"'".to_owned()),
+			Event::TextDeclEnd(&mut b"?>".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EMPTY(&mut b"EMPTY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::INCLUDE(&mut b"INCLUDE".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::IgnoreSectChunk(&mut "<".to_owned()),
+			Event::IgnoreSectChunk(&mut "!ELEMENT a ANY>".to_owned()),
+			Event::IgnoreSectChunk(&mut "<![".to_owned()),
+			Event::IgnoreSectChunk(&mut "x".to_owned()),
+			Event::IgnoreSectChunk(&mut "]]>".to_owned()),
+			Event::IgnoreSectChunk(&mut "]".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn external_subset_drop_ignored_content() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0).with_drop_ignored_content(true),
+		"<?xml encoding='UTF-8'?><!ELEMENT a EMPTY><![INCLUDE[ <!ATTLIST a> ]]><![ IGNORE [<!ELEMENT a ANY><![x]]>]]]>%p;",
+		&[
+			Event::TextDeclStart(&mut b"<?xml".to_owned()),
+			Event::EncNameChunk(&mut b"UTF-8".to_owned()),
+			Event::EncNameEnd(&mut b"'".to_owned()),
+			Event::TextDeclEnd(&mut b"?>".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EMPTY(&mut b"EMPTY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::INCLUDE(&mut b"INCLUDE".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn external_subset_unbalanced() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<!ELEMENT a ANY>]]>",
+		&[
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ANY(&mut b"ANY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected31ExtSubsetDecl)),
+	);
+}
+
+#[test]
+fn external_subset_parameter_entity_references_in_markupdecl() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<!ELEMENT a %c;><!ELEMENT %b; (%p; | c)*><!ELEMENT c (#PCDATA | %i;)*><!ATTLIST a %atts;><!ATTLIST c d %t; %d; e CDATA %d;>",
+		&[
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "p".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::Repetition(Repetition::ZeroOrMore(&mut b"*".to_owned())),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::GroupStart(&mut b"(".to_owned()),
+			Event::PCDATA(&mut b"#PCDATA".to_owned()),
+			Event::ChoiceSeparator(&mut b"|".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "i".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::GroupEnd(&mut b")".to_owned()),
+			Event::Repetition(Repetition::ZeroOrMore(&mut b"*".to_owned())),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "atts".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+			Event::AttlistDeclStart(&mut b"<!ATTLIST".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::AttributeNameChunk(&mut "d".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "t".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "d".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::AttributeNameChunk(&mut "e".to_owned()),
+			Event::StringType(StringType::CDATA(&mut b"CDATA".to_owned())),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "d".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::AttlistDeclEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn internal_subset_parameter_entity_reference_in_markupdecl() {
+	setup();
+
+	expect_events(
+		"<!DOCTYPE a[<!ELEMENT a %c;>]><a/>",
+		&[
+			Event::DoctypedeclStart(&mut b"<!DOCTYPE".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected46ContentSpec)),
+	);
+}
+
+#[test]
+fn conditional_section_nested_ignore() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<![IGNORE[a<![INCLUDE[<![b]]>]]>]]><!ELEMENT c ANY>",
+		&[
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::IgnoreSectChunk(&mut "a".to_owned()),
+			Event::IgnoreSectChunk(&mut "<![".to_owned()),
+			Event::IgnoreSectChunk(&mut "INCLUDE[".to_owned()),
+			Event::IgnoreSectChunk(&mut "<![".to_owned()),
+			Event::IgnoreSectChunk(&mut "b".to_owned()),
+			Event::IgnoreSectChunk(&mut "]]>".to_owned()),
+			Event::IgnoreSectChunk(&mut "]]>".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::ElementDeclStart(&mut b"<!ELEMENT".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::ANY(&mut b"ANY".to_owned()),
+			Event::ElementDeclEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn conditional_section_nested_ignore_dropped() {
+	setup();
+
+	expect_scanner_events_fed(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0)
+			.with_drop_ignored_content(true),
+		&["<![IGNORE[a<![<![b]", "]>]]>]", "]><![ INCLUDE [", "]]>"],
+		&[
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::INCLUDE(&mut b"INCLUDE".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn conditional_section_parameter_entity_keyword() {
+	setup();
+
+	// The caller feeds the replacement text of `%draft;` right after the reference.
+	expect_scanner_events_fed(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		&["<![%draft;", " IGNORE ", "[<!ELEMENT a ANY>]]>"],
+		&[
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "draft".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::IgnoreSectChunk(&mut "<".to_owned()),
+			Event::IgnoreSectChunk(&mut "!ELEMENT a ANY>".to_owned()),
+			Event::ConditionalSectEnd(&mut b"]]>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn conditional_section_parameter_entity_keyword_unexpanded() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<![ %draft;[<!ELEMENT a ANY>]]>",
+		&[
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::PEReferenceStart(&mut b"%".to_owned()),
+			Event::NameChunk(&mut "draft".to_owned()),
+			Event::PEReferenceEnd(&mut b";".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedINCLUDEorIGNORE)),
+	);
+}
+
+#[test]
+fn conditional_section_unterminated_ignore() {
+	setup();
+
+	// The rest of the input is ignored until the section ends.
+	expect_scanner_events(
+		Scanner::new_external_subset(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<![IGNORE[<![a]]><!ELEMENT b ANY>",
+		&[
+			Event::ConditionalSectStart(&mut b"<![".to_owned()),
+			Event::IGNORE(&mut b"IGNORE".to_owned()),
+			Event::IgnoreSectChunk(&mut "<![".to_owned()),
+			Event::IgnoreSectChunk(&mut "a".to_owned()),
+			Event::IgnoreSectChunk(&mut "]]>".to_owned()),
+			Event::IgnoreSectChunk(&mut "<".to_owned()),
+			Event::IgnoreSectChunk(&mut "!ELEMENT b ANY>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn external_parsed_entity() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_1),
+		"<?xml version='1.0' encoding=\"UTF-8\" ?>a<b/>c",
+		&[
+			Event::TextDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::EncNameChunk(&mut b"UTF-8".to_owned()),
+			Event::EncNameEnd(&mut b"\"".to_owned()),
+			Event::TextDeclEnd(&mut b"?>".to_owned()),
+			Event::CharDataChunk(&mut "a".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+			Event::CharDataChunk(&mut "c".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn external_parsed_entity_without_text_declaration() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<?xml-stylesheet?>a",
+		&[
+			Event::PIStart(&mut b"<?".to_owned()),
+			Event::NameChunk(&mut "xml-stylesheet".to_owned()),
+			Event::PIEnd(&mut b"?>".to_owned()),
+			Event::CharDataChunk(&mut "a".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn external_parsed_entity_unexpected_end_tag() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"a</b>",
+		&[Event::CharDataChunk(&mut "a".to_owned())],
+		Some(ScannerError::XmlError(Error::UnexpectedEndTag)),
+	);
+}
+
+#[test]
+fn text_declaration_missing_encoding() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_0),
+		"<?xml version='1.0'?>",
+		&[
+			Event::TextDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.".to_owned()),
+			Event::VersionChunk(&mut b"0".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected80EncodingDecl)),
+	);
+}
+
+#[test]
+fn external_parsed_entity_version() {
+	setup();
+
+	let mut buffer = Vec::from_iter(
+		"<?xml version='1.0' encoding='UTF-8'?>"
+			.bytes()
+			.map(MaybeUninit::new),
+	);
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	unsafe {
+		buffer.assume_filled_n_remaining(buffer.remaining_len());
+	}
+
+	let mut scanner = Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_1);
+	while !matches!(
+		scanner.resume(&mut buffer).unwrap().unwrap().unwrap(),
+		Event::TextDeclEnd(_)
+	) {}
+	assert_eq!(scanner.version(), Some(XmlVersion::Xml1_1));
+}
+
+#[test]
+fn empty_element() {
+	setup();
+
+	expect_events(
+		"<empty />",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "empty".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_content() {
+	setup();
+
+	expect_events(
+		"<element > content </element>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut " content ".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_content_1_0_fail() {
+	setup();
+
+	expect_events(
+		"<element > content \u{1} </element>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut " content ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected2Char)),
+	);
+}
+
+#[test]
+fn element_content_1_1() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1'?><element> content &#x1; </element>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut " content ".to_owned()),
+			Event::CharRefHexadecimalStart(&mut b"&#x".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"1".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), '\u{1}'),
+			Event::CharDataChunk(&mut " ".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_content_1_1_restricted_fail() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1'?><element> content \u{1} </element>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut " content ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::RestrictedChar)),
+	);
+}
+
+#[test]
+fn element_content_1_1_fail() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1'?><element> content \u{0} </element>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "element".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut " content ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected2Char)),
+	);
+}
+
+#[test]
+fn element_content_cdend_fail() {
+	setup();
+
+	expect_events(
+		"<a>x]]>y</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut "x".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::CDEndInCharData)),
+	);
+}
+
+#[test]
+fn element_content_stray_ampersand_fail() {
+	setup();
+
+	expect_events(
+		"<a>x & y</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut "x ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::StrayAmpersand)),
+	);
+}
+
+#[test]
+fn element_content_stray_less_than_fail() {
+	setup();
+
+	expect_events_fed(
+		&["<a>x <", "1/></a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut "x ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::StrayLessThan)),
+	);
+}
+
+#[test]
+fn attribute() {
+	setup();
+
+	expect_events(
+		"<a x=\"1\"/>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"\"".to_owned()),
+			Event::AttValueChunk(&mut "1".to_owned()),
+			Event::AttValueEnd(&mut b"\"".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn attributes_with_references() {
+	setup();
+
+	expect_events(
+		"<a x = 'a\"&amp;&#x62;' y=\"'\"></a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "a\"".to_owned()),
+			Event::EntityRefStart(&mut b"&".to_owned()),
+			Event::NameChunk(&mut "amp".to_owned()),
+			Event::EntityRefEnd(&mut b";".to_owned()),
+			Event::CharRefHexadecimalStart(&mut b"&#x".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"62".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), 'b'),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "y".to_owned()),
+			Event::AttValueStart(&mut b"\"".to_owned()),
+			Event::AttValueChunk(&mut "'".to_owned()),
+			Event::AttValueEnd(&mut b"\"".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn character_reference_split() {
+	setup();
+
+	expect_events_fed(
+		&["<a>&#x1", "F600;&#", "1", "0;</a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharRefHexadecimalStart(&mut b"&#x".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"1".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"F600".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), '\u{1F600}'),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"1".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"0".to_owned()),
+			Event::CharRefEnd(&mut b";".to_owned(), '\n'),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn character_reference_1_0_illegal() {
+	setup();
+
+	expect_events(
+		"<a>&#1;</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"1".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::IllegalCharRef)),
+	);
+}
+
+#[test]
+fn character_reference_1_1_illegal() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1'?><a b='&#x0;'/>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::CharRefHexadecimalStart(&mut b"&#x".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"0".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::IllegalCharRef)),
+	);
+}
+
+#[test]
+fn character_reference_surrogate() {
+	setup();
+
+	expect_events(
+		"<a>&#xD800;</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharRefHexadecimalStart(&mut b"&#x".to_owned()),
+			Event::CharRefHexadecimalChunk(&mut b"D800".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::IllegalCharRef)),
+	);
+}
+
+#[test]
+fn character_reference_overflow() {
+	setup();
+
+	expect_events(
+		"<a>&#99999999999;</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharRefDecimalStart(&mut b"&#".to_owned()),
+			Event::CharRefDecimalChunk(&mut b"99999999999".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::IllegalCharRef)),
+	);
+}
+
+#[test]
+fn attribute_less_than_error() {
+	setup();
+
+	expect_events(
+		"<a x='1<2'/>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "1".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::UnexpectedSequence(b"<"))),
+	);
+}
+
+#[test]
+fn cdata() {
+	setup();
+
+	expect_events(
+		"<a><![CDATA[<b>]]&]]></a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
+			Event::CDataChunk(&mut "<b>]]&".to_owned()),
+			Event::CDEnd(&mut b"]]>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn cdata_empty() {
+	setup();
+
+	expect_events(
+		"<a><![CDATA[]]></a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
+			Event::CDEnd(&mut b"]]>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn cdata_split_end() {
+	setup();
+
+	expect_events_fed(
+		&["<a><![CDATA[x]", "]>", "</a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
+			Event::CDataChunk(&mut "x".to_owned()),
+			Event::CDEnd(&mut b"]]>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
 		None,
 	);
 }
 
 #[test]
-fn downgrade_1() {
+fn cdata_split_not_end() {
 	setup();
 
-	expect_events(" ", &[], None);
+	expect_events_fed(
+		&["<a><![CDATA[x]", "]", "x]]></a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
+			Event::CDataChunk(&mut "x".to_owned()),
+			Event::CDataChunk(&mut "]]x".to_owned()),
+			Event::CDEnd(&mut b"]]>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
 }
 
 #[test]
-fn downgrade_2() {
+fn cdata_1_0_fail() {
 	setup();
 
 	expect_events(
-		"<?xml version=\"1.0\"?>",
+		"<a><![CDATA[\u{1}]]></a>",
 		&[
-			Event::VersionChunk(&mut b"1.".to_owned()),
-			Event::VersionChunk(&mut b"0".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
 		],
-		None,
+		Some(ScannerError::XmlError(Error::Expected2Char)),
 	);
 }
 
 #[test]
-fn downgrade_3() {
+fn cdata_1_1_restricted_fail() {
 	setup();
 
 	expect_events(
-		"<?xml version=\"1.12345\"?>",
+		"<?xml version='1.1'?><a><![CDATA[\u{1}]]></a>",
 		&[
-			Event::VersionChunk(&mut b"1.".to_owned()),
-			Event::VersionChunk(&mut b"12345".to_owned()),
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CDStart(&mut b"<![CDATA[".to_owned()),
 		],
-		None,
+		Some(ScannerError::XmlError(Error::RestrictedChar)),
 	);
 }
 
 #[test]
-fn downgrade_4() {
+fn attribute_c1_controls() {
 	setup();
 
 	expect_events(
-		"<?xml version=\"1.77777\"?>",
+		"<a b='\u{80}\u{85}'/>",
 		&[
-			Event::VersionChunk(&mut b"1.".to_owned()),
-			Event::VersionChunk(&mut b"77777".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "\u{80}\u{85}".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
 		],
 		None,
 	);
 }
 
 #[test]
-fn empty_element() {
+fn attribute_c1_controls_1_1_restricted_fail() {
 	setup();
 
 	expect_events(
-		"<empty />",
+		"<?xml version='1.1'?><a b='\u{85}\u{80}'/>",
 		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
 			Event::StartTagStart(&mut b"<".to_owned()),
-			Event::NameChunk(&mut "empty".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "\n".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::RestrictedChar)),
+	);
+}
+
+#[test]
+fn processing_instruction() {
+	setup();
+
+	expect_events(
+		"<?xml-stylesheet href='a'?><a/>",
+		&[
+			Event::PIStart(&mut b"<?".to_owned()),
+			Event::NameChunk(&mut "xml-stylesheet".to_owned()),
+			Event::PIChunk(&mut "href='a'".to_owned()),
+			Event::PIEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
 		],
 		None,
@@ -83,18 +2035,20 @@ fn empty_element() {
 }
 
 #[test]
-fn element_content() {
+fn processing_instruction_empty() {
 	setup();
 
 	expect_events(
-		"<element > content </element>",
+		"<a><?x?></a>",
 		&[
 			Event::StartTagStart(&mut b"<".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::StartTagEnd(&mut b">".to_owned()),
-			Event::CharDataChunk(&mut " content ".to_owned()),
+			Event::PIStart(&mut b"<?".to_owned()),
+			Event::NameChunk(&mut "x".to_owned()),
+			Event::PIEnd(&mut b"?>".to_owned()),
 			Event::EndTagStart(&mut b"</".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::EndTagEnd(&mut b">".to_owned()),
 		],
 		None,
@@ -102,35 +2056,145 @@ fn element_content() {
 }
 
 #[test]
-fn element_content_1_0_fail() {
+fn processing_instruction_reserved() {
 	setup();
 
 	expect_events(
-		"<element > content \u{1} </element>",
+		"<?XmL?><a/>",
+		&[Event::PIStart(&mut b"<?".to_owned())],
+		Some(ScannerError::XmlError(Error::ReservedPITarget)),
+	);
+}
+
+#[test]
+fn misplaced_xml_declaration() {
+	setup();
+
+	expect_events(
+		"<?xml version='1.1'?><?xml version='1.1'?><a/>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::PIStart(&mut b"<?".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::MisplacedXMLDecl)),
+	);
+}
+
+#[test]
+fn misplaced_xml_declaration_after_whitespace() {
+	setup();
+
+	expect_events(
+		" <?xml version='1.0'?><a/>",
+		&[Event::PIStart(&mut b"<?".to_owned())],
+		Some(ScannerError::XmlError(Error::MisplacedXMLDecl)),
+	);
+}
+
+#[test]
+fn comment_empty() {
+	setup();
+
+	expect_events(
+		"<!---->",
+		&[
+			Event::CommentStart(&mut b"<!--".to_owned()),
+			Event::CommentEnd(&mut b"-->".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn comment() {
+	setup();
+
+	expect_events(
+		"<!-- -->",
+		&[
+			Event::CommentStart(&mut b"<!--".to_owned()),
+			Event::CommentChunk(&mut " ".to_owned()),
+			Event::CommentEnd(&mut b"-->".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn comment_error() {
+	setup();
+
+	expect_events(
+		"<!-- -- -->",
+		&[
+			Event::CommentStart(&mut b"<!--".to_owned()),
+			Event::CommentChunk(&mut " ".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::DoubleDashInComment)),
+	);
+}
+
+#[test]
+fn line_endings() {
+	setup();
+
+	expect_events(
+		"<a>x\r\ny\rz</a>",
 		&[
 			Event::StartTagStart(&mut b"<".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::StartTagEnd(&mut b">".to_owned()),
-			Event::CharDataChunk(&mut " content ".to_owned()),
+			Event::CharDataChunk(&mut "x\ny\nz".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
 		],
-		Some(ScannerError::XmlError(Error::Expected42ETag)),
+		None,
 	);
 }
 
 #[test]
-fn element_content_1_1() {
+fn line_endings_split() {
+	setup();
+
+	expect_events_fed(
+		&["<a>x\r", "\ny</a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::CharDataChunk(&mut "x".to_owned()),
+			Event::CharDataChunk(&mut "\ny".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn line_endings_1_1() {
 	setup();
 
 	expect_events(
-		"<?xml version='1.1'?><element> content \u{1} </element>",
+		"<?xml version='1.1'?><a b='\u{85}\r\u{85}\u{2028}'>\r\n\u{85}</a>",
 		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
 			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
 			Event::StartTagStart(&mut b"<".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "\n\n\n".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
 			Event::StartTagEnd(&mut b">".to_owned()),
-			Event::CharDataChunk(&mut " content \u{1} ".to_owned()),
+			Event::CharDataChunk(&mut "\n\n".to_owned()),
 			Event::EndTagStart(&mut b"</".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::EndTagEnd(&mut b">".to_owned()),
 		],
 		None,
@@ -138,30 +2202,119 @@ fn element_content_1_1() {
 }
 
 #[test]
-fn element_content_1_1_fail() {
+fn whitespace_line_endings_1_1() {
+	setup();
+
+	expect_events_fed(
+		&[
+			"<?xml version='1.1'?><a\u{85}b='c'".as_bytes(),
+			b"\xE2\x80",
+			b"\xA8/>",
+		],
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "b".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "c".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn whitespace_line_endings_1_1_xml_declaration() {
 	setup();
 
 	expect_events(
-		"<?xml version='1.1'?><element> content \u{0} </element>",
+		"<?xml version='1.1'\u{85}?><a/>",
+		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedXMLDeclEnd)),
+	);
+	expect_events(
+		"<?xml version='1.1' encoding='UTF-8'\u{2028}?><a/>",
 		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
 			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::EncNameChunk(&mut b"UTF-8".to_owned()),
+			Event::EncNameEnd(&mut b"'".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedXMLDeclEnd)),
+	);
+}
+
+#[test]
+fn whitespace_line_endings_1_1_text_declaration() {
+	setup();
+
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_1),
+		"<?xml encoding='UTF-8'\u{85}?>a",
+		&[
+			Event::TextDeclStart(&mut b"<?xml".to_owned()),
+			Event::EncNameChunk(&mut b"UTF-8".to_owned()),
+			Event::EncNameEnd(&mut b"'".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedTextDeclEnd)),
+	);
+	expect_scanner_events(
+		Scanner::new_external_parsed_entity(DEPTH_LIMIT, XmlVersion::Xml1_1),
+		"<?xml encoding\u{2028}='UTF-8'?>a",
+		&[Event::TextDeclStart(&mut b"<?xml".to_owned())],
+		Some(ScannerError::XmlError(Error::ExpectedLiteral(b"="))),
+	);
+}
+
+#[test]
+fn whitespace_line_endings_1_0() {
+	setup();
+
+	expect_events(
+		"<a\u{85}b='c'/>",
+		&[
 			Event::StartTagStart(&mut b"<".to_owned()),
-			Event::NameChunk(&mut "element".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::ExpectedStartTagEnd)),
+	);
+}
+
+#[test]
+fn line_endings_1_0() {
+	setup();
+
+	expect_events(
+		"<a>\u{85}\u{2028}</a>",
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
 			Event::StartTagEnd(&mut b">".to_owned()),
-			Event::CharDataChunk(&mut " content ".to_owned()),
+			Event::CharDataChunk(&mut "\u{85}\u{2028}".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
 		],
-		Some(ScannerError::XmlError(Error::Expected42ETag)),
+		None,
 	);
 }
 
 #[test]
-fn comment_empty() {
+fn comment_line_endings() {
 	setup();
 
 	expect_events(
-		"<!---->",
+		"<!--a\r\nb-->",
 		&[
 			Event::CommentStart(&mut b"<!--".to_owned()),
+			Event::CommentChunk(&mut "a\nb".to_owned()),
 			Event::CommentEnd(&mut b"-->".to_owned()),
 		],
 		None,
@@ -169,42 +2322,82 @@ fn comment_empty() {
 }
 
 #[test]
-fn comment() {
+fn comment_char_fail() {
 	setup();
 
 	expect_events(
-		"<!-- -->",
+		"<!--a\u{0}-->",
 		&[
 			Event::CommentStart(&mut b"<!--".to_owned()),
-			Event::CommentChunk(&mut " ".to_owned()),
-			Event::CommentEnd(&mut b"-->".to_owned()),
+			Event::CommentChunk(&mut "a".to_owned()),
 		],
-		None,
+		Some(ScannerError::XmlError(Error::Expected2Char)),
 	);
 }
 
 #[test]
-fn comment_error() {
+fn comment_1_1_restricted_fail() {
 	setup();
 
 	expect_events(
-		"<!-- -- -->",
+		"<?xml version='1.1'?><!--\u{1}-->",
 		&[
+			Event::XmlDeclStart(&mut b"<?xml".to_owned()),
+			Event::VersionChunk(&mut b"1.1".to_owned()),
+			Event::XmlDeclEnd(&mut b"?>".to_owned()),
 			Event::CommentStart(&mut b"<!--".to_owned()),
-			Event::CommentChunk(&mut " ".to_owned()),
 		],
-		Some(ScannerError::XmlError(Error::DoubleDashInComment)),
+		Some(ScannerError::XmlError(Error::RestrictedChar)),
+	);
+}
+
+#[test]
+fn comment_dash_before_end() {
+	setup();
+
+	expect_events_fed(
+		&["<!--a-", "-", "->"],
+		&[
+			Event::CommentStart(&mut b"<!--".to_owned()),
+			Event::CommentChunk(&mut "a".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::DashBeforeCommentEnd)),
+	);
+}
+
+#[test]
+fn processing_instruction_char_fail() {
+	setup();
+
+	expect_events(
+		"<?a b\u{FFFF}?>",
+		&[
+			Event::PIStart(&mut b"<?".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::PIChunk(&mut "b".to_owned()),
+		],
+		Some(ScannerError::XmlError(Error::Expected2Char)),
 	);
 }
 
+const DEPTH_LIMIT: usize = 16;
+
 fn expect_events(input: impl AsRef<[u8]>, events: &[Event], error: Option<ScannerError>) {
+	expect_scanner_events(Scanner::new(DEPTH_LIMIT), input, events, error);
+}
+
+fn expect_scanner_events(
+	mut scanner: Scanner,
+	input: impl AsRef<[u8]>,
+	events: &[Event],
+	error: Option<ScannerError>,
+) {
 	let mut buffer = Vec::from_iter(input.as_ref().iter().copied().map(MaybeUninit::new));
 	let mut buffer = StrBuf::new(buffer.as_mut_slice());
 	unsafe {
 		buffer.assume_filled_n_remaining(buffer.remaining_len());
 	}
 
-	let mut scanner = Scanner::new(10);
 	for expected in events {
 		let _span = info_span!("Expecting event", expected = ?expected).entered();
 		assert_eq!(
@@ -225,6 +2418,79 @@ fn expect_events(input: impl AsRef<[u8]>, events: &[Event], error: Option<Scanne
 	}
 }
 
+fn version_after_events(input: &str, count: usize) -> Option<XmlVersion> {
+	let mut buffer = Vec::from_iter(input.bytes().map(MaybeUninit::new));
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	unsafe {
+		buffer.assume_filled_n_remaining(buffer.remaining_len());
+	}
+
+	let mut scanner = Scanner::new(DEPTH_LIMIT);
+	for _ in 0..count {
+		scanner.resume(&mut buffer).unwrap().unwrap().unwrap();
+	}
+	scanner.version()
+}
+
+/// Like [`expect_events`], but only makes `input` available one piece at a time,
+/// whenever the scanner asks for more.
+fn expect_events_fed(input: &[impl AsRef<[u8]>], events: &[Event], error: Option<ScannerError>) {
+	expect_scanner_events_fed(Scanner::new(DEPTH_LIMIT), input, events, error);
+}
+
+fn expect_scanner_events_fed(
+	mut scanner: Scanner,
+	input: &[impl AsRef<[u8]>],
+	events: &[Event],
+	error: Option<ScannerError>,
+) {
+	let mut buffer =
+		vec![MaybeUninit::uninit(); input.iter().map(|piece| piece.as_ref().len()).sum()];
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	let mut input = input.iter().map(AsRef::as_ref);
+	let mut feed = |buffer: &mut StrBuf| {
+		let piece = input.next().expect("Ran out of input.");
+		unsafe {
+			buffer.unshift_reset();
+			for (slot, byte) in buffer.remaining_maybe_uninitialized().iter_mut().zip(piece) {
+				slot.write(*byte);
+			}
+			buffer.assume_filled_n_remaining(piece.len());
+		}
+	};
+
+	for expected in events {
+		let _span = info_span!("Expecting event", expected = ?expected).entered();
+		loop {
+			match scanner.resume(&mut buffer) {
+				Ok(result) => {
+					assert_eq!(result.unwrap().unwrap(), *expected);
+					break;
+				}
+				Err(_) => feed(&mut buffer),
+			}
+		}
+	}
+
+	if let Some(error) = error {
+		let _span = info_span!("Expecting error").entered();
+		loop {
+			match scanner.resume(&mut buffer) {
+				Ok(result) => {
+					assert_eq!(result.unwrap_err(), error);
+					break;
+				}
+				Err(_) => feed(&mut buffer),
+			}
+		}
+	} else {
+		let _span = info_span!("Expecting needs more data").entered();
+		scanner.resume(&mut buffer).unwrap_err();
+		assert_eq!(buffer.filled().len(), 0);
+		assert!(input.next().is_none(), "Not all input was consumed.");
+	}
+}
+
 static SETUP_ONCE: Once = Once::new();
 fn setup() {
 	SETUP_ONCE.call_once(|| {
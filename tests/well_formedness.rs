@@ -0,0 +1,330 @@
+use std::mem::MaybeUninit;
+use yolo_xml::{
+	buffer::StrBuf,
+	scanner::{Event, Scanner},
+	well_formedness::{Checker, WellFormednessError},
+};
+
+#[test]
+fn element_type_match() {
+	expect_events(
+		checker(),
+		&["<a><b", "c></bc></a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::NameChunk(&mut "c".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "bc".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn element_type_mismatch() {
+	expect_events(
+		checker(),
+		&["<a><b/></ab>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "ab".to_owned()),
+		],
+		Some(WellFormednessError::ElementTypeMismatch {
+			start_tag: "a".into(),
+			end_tag: "ab".into(),
+		}),
+	);
+}
+
+#[test]
+fn name_budget() {
+	expect_events(
+		Checker::new(Scanner::new(DEPTH_LIMIT), 3),
+		&["<a><bc><d"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "bc".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+		],
+		Some(WellFormednessError::NameBudgetExceeded),
+	);
+}
+
+#[test]
+fn unique_attributes() {
+	expect_events(
+		checker(),
+		&["<a x='1' y='2'><b x='3'/></a>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "1".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "y".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "2".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEnd(&mut b">".to_owned()),
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "b".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "3".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::StartTagEndEmpty(&mut b"/>".to_owned()),
+			Event::EndTagStart(&mut b"</".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::EndTagEnd(&mut b">".to_owned()),
+		],
+		None,
+	);
+}
+
+#[test]
+fn duplicate_attribute() {
+	expect_events(
+		checker(),
+		&["<a xy='1' x", "y=\"2\"/>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "xy".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "1".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttributeNameChunk(&mut "y".to_owned()),
+		],
+		Some(WellFormednessError::DuplicateAttribute("xy".into())),
+	);
+}
+
+#[test]
+fn attribute_limit() {
+	expect_events(
+		checker().with_attribute_limit(1),
+		&["<a x='1' y='2'/>"],
+		&[
+			Event::StartTagStart(&mut b"<".to_owned()),
+			Event::NameChunk(&mut "a".to_owned()),
+			Event::AttributeNameChunk(&mut "x".to_owned()),
+			Event::AttValueStart(&mut b"'".to_owned()),
+			Event::AttValueChunk(&mut "1".to_owned()),
+			Event::AttValueEnd(&mut b"'".to_owned()),
+			Event::AttributeNameChunk(&mut "y".to_owned()),
+		],
+		Some(WellFormednessError::AttributeLimitExceeded),
+	);
+}
+
+#[test]
+fn declared_entities() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY e '&lt;&f;'><!ENTITY f 'f'><!ENTITY e '&e;'>]><a b='&e;'>&e;&amp;</a>"],
+		None,
+	);
+}
+
+#[test]
+fn undeclared_entity() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY e '&f;'>]><a>&e;</a>"],
+		Some(WellFormednessError::UndeclaredEntity("f".into())),
+	);
+}
+
+#[test]
+fn undeclared_entity_external_subset() {
+	expect_outcome(checker(), &["<!DOCTYPE a SYSTEM 'a.dtd'><a>&e;</a>"], None);
+}
+
+#[test]
+fn undeclared_entity_standalone() {
+	expect_outcome(
+		checker(),
+		&["<?xml version='1.0' standalone='yes'?><!DOCTYPE a SYSTEM 'a.dtd'><a>&e;</a>"],
+		Some(WellFormednessError::UndeclaredEntity("e".into())),
+	);
+}
+
+#[test]
+fn declarations_after_parameter_entity_reference() {
+	let input =
+		"<!DOCTYPE a [<!ENTITY % p SYSTEM 'p.ent'>%p;<!ENTITY e SYSTEM 'e' NDATA n>]><a>&e;&f;</a>";
+	expect_outcome(checker(), &[input], None);
+	expect_outcome(
+		checker(),
+		&[format!("<?xml version='1.0' standalone='yes'?>{}", input)],
+		Some(WellFormednessError::UnparsedEntityReference("e".into())),
+	);
+}
+
+#[test]
+fn unparsed_entity_reference() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!NOTATION n SYSTEM 'n'><!ENTITY e SYSTEM 'e' NDATA n>]><a>&e;</a>"],
+		Some(WellFormednessError::UnparsedEntityReference("e".into())),
+	);
+}
+
+#[test]
+fn recursive_entity_reference() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY x '&y;'><!ENTITY y '&lt;&x;'>]><a>&x;</a>"],
+		Some(WellFormednessError::RecursiveEntityReference("x".into())),
+	);
+}
+
+#[test]
+fn recursive_entity_reference_through_character_reference() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY x '&#38;x;'>]><a>&x;</a>"],
+		Some(WellFormednessError::RecursiveEntityReference("x".into())),
+	);
+}
+
+#[test]
+fn undeclared_entity_through_character_reference() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY x '&#38;nope;'>]><a>&x;</a>"],
+		Some(WellFormednessError::UndeclaredEntity("nope".into())),
+	);
+}
+
+#[test]
+fn external_entity_reference_in_attribute_value() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY x SYSTEM 'x.xml'><!ENTITY y '&x;'>]><a>&y;<b c='&y;'/></a>"],
+		Some(WellFormednessError::ExternalEntityReferenceInAttributeValue("x".into())),
+	);
+}
+
+#[test]
+fn less_than_in_attribute_value() {
+	expect_outcome(
+		checker(),
+		&["<!DOCTYPE a [<!ENTITY x '&#60;'><!ENTITY y '&x;'>]><a>&y;<b c='&y;'/></a>"],
+		Some(WellFormednessError::LessThanInAttributeValue("x".into())),
+	);
+}
+
+const DEPTH_LIMIT: usize = 16;
+const NAME_BUDGET: usize = 64;
+
+fn checker() -> Checker {
+	Checker::new(Scanner::new(DEPTH_LIMIT), NAME_BUDGET)
+}
+
+/// Like [`expect_events`], but only checks the outcome.
+fn expect_outcome(
+	mut checker: Checker,
+	input: &[impl AsRef<[u8]>],
+	error: Option<WellFormednessError>,
+) {
+	let mut buffer =
+		vec![MaybeUninit::uninit(); input.iter().map(|piece| piece.as_ref().len()).sum()];
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	let mut input = input.iter().map(AsRef::as_ref);
+
+	let outcome = loop {
+		match checker.resume(&mut buffer) {
+			Ok(Ok(Some(_))) => (),
+			Ok(Ok(None)) => break None,
+			Ok(Err(error)) => break Some(error),
+			Err(_) => match input.next() {
+				Some(piece) => unsafe {
+					buffer.unshift_reset();
+					for (slot, byte) in buffer.remaining_maybe_uninitialized().iter_mut().zip(piece)
+					{
+						slot.write(*byte);
+					}
+					buffer.assume_filled_n_remaining(piece.len());
+				},
+				None => break None,
+			},
+		}
+	};
+	if outcome.is_none() {
+		assert_eq!(buffer.filled().len(), 0);
+	}
+	assert_eq!(outcome, error);
+}
+
+/// Makes `input` available one piece at a time, whenever the checker asks for more.
+fn expect_events(
+	mut checker: Checker,
+	input: &[impl AsRef<[u8]>],
+	events: &[Event],
+	error: Option<WellFormednessError>,
+) {
+	let mut buffer =
+		vec![MaybeUninit::uninit(); input.iter().map(|piece| piece.as_ref().len()).sum()];
+	let mut buffer = StrBuf::new(buffer.as_mut_slice());
+	let mut input = input.iter().map(AsRef::as_ref);
+	let mut feed = |buffer: &mut StrBuf| {
+		let piece = input.next().expect("Ran out of input.");
+		unsafe {
+			buffer.unshift_reset();
+			for (slot, byte) in buffer.remaining_maybe_uninitialized().iter_mut().zip(piece) {
+				slot.write(*byte);
+			}
+			buffer.assume_filled_n_remaining(piece.len());
+		}
+	};
+
+	for expected in events {
+		loop {
+			match checker.resume(&mut buffer) {
+				Ok(result) => {
+					assert_eq!(result.unwrap().unwrap(), *expected);
+					break;
+				}
+				Err(_) => feed(&mut buffer),
+			}
+		}
+	}
+
+	if let Some(error) = error {
+		loop {
+			match checker.resume(&mut buffer) {
+				Ok(result) => {
+					assert_eq!(result.unwrap_err(), error);
+					break;
+				}
+				Err(_) => feed(&mut buffer),
+			}
+		}
+	} else {
+		checker.resume(&mut buffer).unwrap_err();
+		assert_eq!(buffer.filled().len(), 0);
+		assert!(input.next().is_none(), "Not all input was consumed.");
+	}
+}